@@ -1,8 +1,16 @@
 //! Benchmarks for motherduck-supasync operations.
 
+use async_trait::async_trait;
 use criterion::{Criterion, black_box, criterion_group, criterion_main};
+use motherduck_supasync::schema::IntrospectedColumn;
+use motherduck_supasync::source::SourceRow;
+use motherduck_supasync::{
+    MotherDuckClient, Result, SourceCursor, SyncClient, SyncConfig, SyncMode, SyncSource,
+    TableMapping,
+};
 use serde_json::Value as JsonValue;
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Benchmark JSON to SQL string conversion.
 fn bench_json_to_sql(c: &mut Criterion) {
@@ -37,6 +45,153 @@ fn bench_row_map(c: &mut Criterion) {
     });
 }
 
+/// Rows in the table synced by [`bench_pipelined_streaming`].
+const STREAMED_ROWS: usize = 20_000;
+
+/// Time a simulated PostgreSQL fetch of one batch takes.
+const FETCH_LATENCY: Duration = Duration::from_millis(20);
+
+/// Source whose cursor takes [`FETCH_LATENCY`] per batch, like a remote database.
+struct SlowSource;
+
+struct SlowCursor {
+    next_id: usize,
+}
+
+#[async_trait]
+impl SourceCursor for SlowCursor {
+    async fn next_batch(&mut self, size: usize) -> Result<Vec<SourceRow>> {
+        tokio::time::sleep(FETCH_LATENCY).await;
+        let end = (self.next_id + size).min(STREAMED_ROWS);
+        let rows = (self.next_id..end)
+            .map(|id| {
+                HashMap::from([
+                    ("id".to_string(), JsonValue::from(id)),
+                    ("name".to_string(), JsonValue::from(format!("row {}", id))),
+                    ("score".to_string(), JsonValue::from(id as f64 / 7.0)),
+                ])
+            })
+            .collect();
+        self.next_id = end;
+        Ok(rows)
+    }
+
+    async fn mark_synced(&self, keys: &[Vec<JsonValue>]) -> Result<u64> {
+        Ok(keys.len() as u64)
+    }
+
+    async fn close(self: Box<Self>) -> Result<()> {
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) {}
+}
+
+#[async_trait]
+impl SyncSource for SlowSource {
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn table_exists(&self, _table: &str) -> Result<bool> {
+        Ok(true)
+    }
+
+    async fn introspect_table(&self, _table: &str) -> Result<Vec<IntrospectedColumn>> {
+        let column = |name: &str, pg_type: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: pg_type.to_string(),
+            nullable: name != "id",
+            default: None,
+            is_primary_key: name == "id",
+        };
+        Ok(vec![
+            column("id", "bigint"),
+            column("name", "text"),
+            column("score", "double precision"),
+        ])
+    }
+
+    async fn fetch_rows(
+        &self,
+        _mapping: &TableMapping,
+        _full_sync: bool,
+        _limit: Option<usize>,
+        _since: Option<&JsonValue>,
+    ) -> Result<Vec<SourceRow>> {
+        Ok(Vec::new())
+    }
+
+    async fn open_cursor<'a>(
+        &'a self,
+        _mapping: &'a TableMapping,
+        _full_sync: bool,
+        _limit: Option<usize>,
+        _since: Option<&JsonValue>,
+    ) -> Result<Box<dyn SourceCursor + 'a>> {
+        Ok(Box::new(SlowCursor { next_id: 0 }))
+    }
+
+    async fn mark_synced(&self, _mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<u64> {
+        Ok(keys.len() as u64)
+    }
+
+    async fn unsynced_count(
+        &self,
+        _mapping: &TableMapping,
+        _since: Option<&JsonValue>,
+    ) -> Result<i64> {
+        Ok(STREAMED_ROWS as i64)
+    }
+
+    async fn count_rows(&self, _table: &str, _filter: Option<&str>) -> Result<i64> {
+        Ok(STREAMED_ROWS as i64)
+    }
+}
+
+/// Client streaming [`SlowSource`] into an in-memory DuckDB database.
+fn streaming_client(pipeline_depth: usize) -> SyncClient {
+    let mut config = SyncConfig::builder()
+        .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+        .motherduck_local_path(":memory:")
+        .build()
+        .unwrap();
+    config.sync.streaming = true;
+    config.sync.batch_size = 1000;
+    config.sync.pipeline_depth = pipeline_depth;
+    config.tables = vec![
+        TableMapping::builder()
+            .source_table("events")
+            .primary_key(["id"])
+            .enabled(true)
+            .build()
+            .unwrap(),
+    ];
+    let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+    SyncClient::with_backends(config, Box::new(SlowSource), Box::new(sink))
+}
+
+/// Benchmark a streamed full sync with and without fetching ahead of writes.
+fn bench_pipelined_streaming(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("streaming_sync");
+    group.sample_size(10);
+    for (name, depth) in [("serial", 0), ("pipelined", 2)] {
+        let client = streaming_client(depth);
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| async {
+                let result = client.sync(SyncMode::Full).await.unwrap();
+                assert_eq!(result.total_records(), STREAMED_ROWS, "{:?}", result);
+                black_box(result)
+            })
+        });
+    }
+    group.finish();
+}
+
 fn json_to_sql_string(value: &JsonValue) -> String {
     match value {
         JsonValue::Null => "NULL".to_string(),
@@ -47,5 +202,10 @@ fn json_to_sql_string(value: &JsonValue) -> String {
     }
 }
 
-criterion_group!(benches, bench_json_to_sql, bench_row_map);
+criterion_group!(
+    benches,
+    bench_json_to_sql,
+    bench_row_map,
+    bench_pipelined_streaming
+);
 criterion_main!(benches);
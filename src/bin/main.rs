@@ -1,10 +1,23 @@
 //! MotherDuck Sync CLI
 
 use clap::{Parser, Subcommand};
-use motherduck_supasync::{SyncClient, SyncConfig, SyncMode};
+use indicatif::{HumanDuration, MultiProgress, ProgressBar, ProgressStyle};
+use motherduck_supasync::config::LogFormat;
+use motherduck_supasync::metrics::Metrics;
+use motherduck_supasync::sync::SyncPhase;
+use motherduck_supasync::{
+    CancellationToken, ExportFormat, SyncClient, SyncConfig, SyncMode, SyncResult,
+};
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::net::SocketAddr;
 use std::process::ExitCode;
-use tracing::{error, info};
-use tracing_subscriber::{EnvFilter, fmt};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{EnvFilter, Layer, Registry, fmt, reload};
 
 #[derive(Parser)]
 #[command(name = "motherduck-supasync")]
@@ -17,6 +30,14 @@ struct Cli {
     #[arg(long, global = true)]
     full: bool,
 
+    /// Dry run (fetch and count records without writing anything)
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Show the SQL each table's sync runs (with --dry-run, nothing is executed)
+    #[arg(long, global = true)]
+    show_sql: bool,
+
     /// Config file path
     #[arg(short, long, global = true)]
     config: Option<String>,
@@ -25,19 +46,54 @@ struct Cli {
     #[arg(long, default_value = "info", global = true, env = "LOG_LEVEL")]
     log_level: String,
 
-    /// JSON output
+    /// JSON output (logs go to stderr as JSON lines)
     #[arg(long, global = true)]
     json: bool,
 
     /// Quiet mode
     #[arg(short, long, global = true)]
     quiet: bool,
+
+    /// Ignore and clear the sync checkpoint, starting a fresh run
+    #[arg(long, global = true)]
+    restart: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Run sync (default)
-    Sync,
+    Sync {
+        /// Only sync this table (source or target name); repeatable
+        #[arg(short, long = "table")]
+        tables: Vec<String>,
+        /// Sync at most this many rows per table, overriding `sync.max_records`
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Run incremental syncs on an interval until interrupted
+    Watch {
+        /// Seconds between syncs
+        #[arg(short, long, default_value_t = 300, value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// Serve Prometheus metrics on this address (needs the `prometheus` feature)
+        #[arg(long)]
+        metrics_addr: Option<SocketAddr>,
+    },
+    /// Check config and source tables without writing anything
+    Validate,
+    /// Compare row counts between PostgreSQL and MotherDuck
+    Diff {
+        /// Rows a table may differ by before it counts as drifted
+        #[arg(long, default_value_t = 0)]
+        tolerance: u64,
+    },
+    /// Compare two sync results saved as JSON (`--json sync` output or reports)
+    DiffRuns {
+        /// Earlier run
+        previous: String,
+        /// Later run
+        current: String,
+    },
     /// Test connectivity
     Test,
     /// Show unsynced counts
@@ -46,6 +102,9 @@ enum Commands {
     Init {
         #[arg(short, long, default_value = "motherduck-supasync.toml")]
         output: String,
+        /// Print the config JSON Schema to stdout instead
+        #[arg(long)]
+        schema: bool,
     },
     /// Query MotherDuck tables
     Query {
@@ -58,6 +117,9 @@ enum Commands {
         /// List tables
         #[arg(long)]
         tables: bool,
+        /// Output format for --sql results (default: table, or json with --json)
+        #[arg(short, long, value_enum)]
+        format: Option<QueryFormat>,
     },
     /// Clean/reset MotherDuck tables
     Clean {
@@ -68,8 +130,39 @@ enum Commands {
         #[arg(long)]
         truncate: bool,
         /// Specific table to clean
+        #[arg(short, long, conflicts_with = "all")]
+        table: Option<String>,
+        /// Clean every table in the configured schema, not just the synced targets
+        #[arg(long)]
+        all: bool,
+        /// Skip the confirmation prompt
         #[arg(short, long)]
+        yes: bool,
+    },
+    /// Export a MotherDuck table or query result to a Parquet/CSV file
+    Export {
+        /// Table to export
+        #[arg(short, long, required_unless_present = "sql", conflicts_with = "sql")]
         table: Option<String>,
+        /// SQL query whose result to export
+        #[arg(short, long)]
+        sql: Option<String>,
+        /// Output format
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Parquet)]
+        format: ExportFormat,
+        /// Output file path
+        #[arg(short, long)]
+        out: String,
+    },
+    /// Print table mappings for the base tables of a PostgreSQL schema
+    #[command(alias = "generate-config")]
+    Discover {
+        /// PostgreSQL schema to list tables from
+        #[arg(long, default_value = "public")]
+        schema: String,
+        /// Output format (default: toml, or json with --json)
+        #[arg(short, long, value_enum)]
+        format: Option<DiscoverFormat>,
     },
     /// Generate base64 secret from tables.local.json
     GenerateSecret {
@@ -82,9 +175,9 @@ enum Commands {
 #[tokio::main]
 async fn main() -> ExitCode {
     let cli = Cli::parse();
-    init_logging(&cli.log_level, cli.quiet, cli.json);
+    let logging = init_logging(&cli.log_level, cli.quiet, cli.json);
 
-    match run(cli).await {
+    match run(cli, logging).await {
         Ok(_) => ExitCode::SUCCESS,
         Err(e) => {
             error!("Error: {}", e);
@@ -93,9 +186,16 @@ async fn main() -> ExitCode {
     }
 }
 
-async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+async fn run(cli: Cli, logging: LogHandles) -> Result<(), Box<dyn std::error::Error>> {
     // Handle init command first - it doesn't need config
-    if let Some(Commands::Init { output }) = cli.command {
+    if let Some(Commands::Init { output, schema }) = cli.command {
+        if schema {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&SyncConfig::json_schema())?
+            );
+            return Ok(());
+        }
         return run_init(&output);
     }
 
@@ -104,24 +204,123 @@ async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
         return run_generate_secret(&input);
     }
 
+    // Handle diff-runs command - only reads the two result files
+    if let Some(Commands::DiffRuns { previous, current }) = cli.command {
+        return run_diff_runs(&previous, &current, cli.json);
+    }
+
     let config = load_config(cli.config.as_deref())?;
+    if config.logging.format == LogFormat::Json && !cli.quiet && !cli.json {
+        logging
+            .format
+            .reload(Some(fmt_layer(LogFormat::Json, cli.json)))?;
+    }
+    let _otel_guard = start_otel(logging.otel, config.logging.otlp_endpoint.as_deref())?;
+
+    if cli.restart {
+        if let Some(ref path) = config.sync.checkpoint_path {
+            motherduck_supasync::checkpoint::Checkpoint::clear(path)?;
+            info!("Cleared checkpoint {}", path);
+        }
+    }
 
     match cli.command {
-        None | Some(Commands::Sync) => run_sync(config, cli.full, cli.json, cli.quiet).await,
+        None => {
+            run_sync(
+                config,
+                &[],
+                None,
+                cli.full,
+                cli.dry_run,
+                cli.show_sql,
+                cli.json,
+                cli.quiet,
+            )
+            .await
+        }
+        Some(Commands::Sync { tables, limit }) => {
+            run_sync(
+                config,
+                &tables,
+                limit,
+                cli.full,
+                cli.dry_run,
+                cli.show_sql,
+                cli.json,
+                cli.quiet,
+            )
+            .await
+        }
+        Some(Commands::Watch {
+            interval,
+            metrics_addr,
+        }) => {
+            run_watch(
+                config,
+                Duration::from_secs(interval),
+                metrics_addr,
+                cli.json,
+                cli.quiet,
+            )
+            .await
+        }
+        Some(Commands::Validate) => run_validate(config, cli.json, cli.quiet).await,
+        Some(Commands::Diff { tolerance }) => {
+            run_diff(config, tolerance, cli.json, cli.quiet).await
+        }
         Some(Commands::Test) => run_test(config, cli.json).await,
         Some(Commands::Status) => run_status(config, cli.json).await,
         Some(Commands::Query {
             sql,
             counts,
             tables,
-        }) => run_query(config, sql, counts, tables, cli.json).await,
+            format,
+        }) => {
+            let format = format.unwrap_or(if cli.json {
+                QueryFormat::Json
+            } else {
+                QueryFormat::Table
+            });
+            run_query(config, sql, counts, tables, format).await
+        }
         Some(Commands::Clean {
             reset,
             truncate,
             table,
-        }) => run_clean(config, reset, truncate, table, cli.json, cli.quiet).await,
+            all,
+            yes,
+        }) => {
+            run_clean(
+                config,
+                CleanOptions {
+                    reset,
+                    truncate,
+                    table,
+                    all,
+                    yes,
+                },
+                cli.json,
+                cli.quiet,
+            )
+            .await
+        }
+        Some(Commands::Export {
+            table,
+            sql,
+            format,
+            out,
+        }) => run_export(config, table, sql, format, &out, cli.json, cli.quiet),
+        Some(Commands::Discover { schema, format }) => {
+            let format = format.unwrap_or(if cli.json {
+                DiscoverFormat::Json
+            } else {
+                DiscoverFormat::Toml
+            });
+            run_discover(config, &schema, format).await
+        }
         Some(Commands::Init { .. }) => unreachable!(), // Handled above
         Some(Commands::GenerateSecret { .. }) => unreachable!(), // Handled above
+        Some(Commands::DiffRuns { .. }) => unreachable!(), // Handled above
     }
 }
 
@@ -131,7 +330,12 @@ fn load_config(path: Option<&str>) -> Result<SyncConfig, Box<dyn std::error::Err
         return Ok(SyncConfig::from_file(p)?);
     }
 
-    for default in &["motherduck-supasync.toml", ".motherduck-supasync.toml"] {
+    for default in &[
+        "motherduck-supasync.toml",
+        ".motherduck-supasync.toml",
+        "motherduck-supasync.yaml",
+        "motherduck-supasync.yml",
+    ] {
         if std::path::Path::new(default).exists() {
             info!("Loading config from: {}", default);
             return Ok(SyncConfig::from_file(default)?);
@@ -142,13 +346,20 @@ fn load_config(path: Option<&str>) -> Result<SyncConfig, Box<dyn std::error::Err
     Ok(SyncConfig::from_env()?)
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_sync(
     config: SyncConfig,
+    tables: &[String],
+    limit: Option<usize>,
     full: bool,
+    dry_run: bool,
+    show_sql: bool,
     json: bool,
     quiet: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mode = if full {
+    let mode = if dry_run {
+        SyncMode::DryRun
+    } else if full {
         SyncMode::Full
     } else {
         SyncMode::Incremental
@@ -159,13 +370,40 @@ async fn run_sync(
         println!("Mode: {}\n", mode);
     }
 
-    let client = SyncClient::new(config).await?;
-    let result = client.sync(mode).await?;
+    let mut config = config;
+    if let Some(limit) = limit {
+        config.sync.max_records = limit;
+    }
+
+    let mut client = SyncClient::new(config).await?;
+    if !quiet && !json && std::io::stdout().is_terminal() {
+        client = client.with_progress(progress_bars());
+    }
+    if show_sql {
+        client = client.with_sql_capture();
+    }
+    let outcome = if tables.is_empty() {
+        client.sync(mode).await
+    } else {
+        let names: Vec<&str> = tables.iter().map(String::as_str).collect();
+        client.sync_tables(mode, &names).await
+    };
+    // An aborted run still reports the tables it got through
+    let result = match outcome {
+        Ok(result) => result,
+        Err(motherduck_supasync::Error::Aborted {
+            partial: Some(partial),
+            ..
+        }) => *partial,
+        Err(e) => return Err(e.into()),
+    };
 
     if json {
         println!("{}", serde_json::to_string_pretty(&result)?);
     } else if !quiet {
-        if result.success {
+        if dry_run {
+            println!("✓ Dry run completed (nothing was written)");
+        } else if result.success {
             println!("✓ Sync completed successfully");
         } else {
             println!("✗ Sync completed with errors");
@@ -173,14 +411,29 @@ async fn run_sync(
         println!("\nDuration: {}ms", result.duration_ms);
         println!("Total records: {}\n", result.total_records());
 
-        for (_, tr) in &result.tables {
+        for tr in result.tables.values() {
             let icon = if tr.success { "✓" } else { "✗" };
+            let verb = if tr.dry_run { "would sync " } else { "" };
             println!(
-                "  {} {} → {}: {} records ({}ms)",
-                icon, tr.source_table, tr.target_table, tr.records_synced, tr.duration_ms
+                "  {} {} → {}: {}{} records ({}ms)",
+                icon, tr.source_table, tr.target_table, verb, tr.records_synced, tr.duration_ms
             );
             if let Some(ref e) = tr.error {
-                println!("      Error: {}", e);
+                println!(
+                    "      Error [{}]: {}",
+                    tr.error_code.as_deref().unwrap_or("UNKNOWN"),
+                    e
+                );
+            }
+            if tr.attempts > 1 {
+                println!(
+                    "      Took {} attempts, last retry: {}",
+                    tr.attempts,
+                    tr.last_retry_reason.as_deref().unwrap_or_default()
+                );
+            }
+            for statement in &tr.sql {
+                println!("      {};", statement);
             }
         }
     }
@@ -192,20 +445,487 @@ async fn run_sync(
     }
 }
 
+/// Longest wait between syncs after repeated failures.
+const MAX_WATCH_BACKOFF: Duration = Duration::from_secs(3600);
+
+async fn run_watch(
+    config: SyncConfig,
+    interval: Duration,
+    metrics_addr: Option<SocketAddr>,
+    json: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !quiet && !json {
+        println!("MotherDuck Sync v{}", motherduck_supasync::VERSION);
+        println!("Watching: incremental sync every {}s\n", interval.as_secs());
+    }
+
+    // Shared across reconnects so counters keep accumulating
+    let metrics = Arc::new(Metrics::new());
+    if let Some(addr) = metrics_addr {
+        serve_metrics(metrics.clone(), addr)?;
+    }
+
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+
+    let mut client: Option<SyncClient> = None;
+    let mut runs = 0usize;
+    let mut failed_runs = 0usize;
+    let mut total_records = 0usize;
+    let mut consecutive_failures = 0u32;
+    let mut stopping = false;
+    let cancel = CancellationToken::new();
+
+    while !stopping {
+        // A signal during a sync lets the current table finish before exiting
+        let pass = watch_pass(&config, &metrics, &mut client, cancel.clone());
+        tokio::pin!(pass);
+        let outcome = loop {
+            tokio::select! {
+                outcome = &mut pass => break outcome,
+                _ = &mut shutdown, if !stopping => {
+                    info!("Shutdown requested, finishing the current table");
+                    cancel.cancel();
+                    stopping = true;
+                }
+            }
+        };
+
+        runs += 1;
+        match outcome {
+            Ok(result) if result.success => {
+                consecutive_failures = 0;
+                total_records += result.total_records();
+                info!(
+                    "Synced {} records in {}ms",
+                    result.total_records(),
+                    result.duration_ms
+                );
+            }
+            Ok(result) => {
+                consecutive_failures += 1;
+                failed_runs += 1;
+                total_records += result.total_records();
+                for tr in result.tables.values().filter(|t| !t.success) {
+                    warn!(
+                        "{} → {} failed: {}",
+                        tr.source_table,
+                        tr.target_table,
+                        tr.error.as_deref().unwrap_or("unknown error")
+                    );
+                }
+            }
+            Err(motherduck_supasync::Error::Cancelled { partial }) => {
+                let records = partial.map_or(0, |r| r.total_records());
+                total_records += records;
+                info!("Sync stopped early after {} records", records);
+            }
+            Err(e) => {
+                consecutive_failures += 1;
+                failed_runs += 1;
+                error!("Sync failed: {}", e);
+            }
+        }
+
+        if stopping {
+            break;
+        }
+
+        let delay = watch_delay(interval, consecutive_failures);
+        if consecutive_failures > 0 {
+            warn!(
+                "{} consecutive failures, next sync in {}s",
+                consecutive_failures,
+                delay.as_secs()
+            );
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(delay) => {}
+            _ = &mut shutdown => stopping = true,
+        }
+    }
+
+    if json {
+        let summary = serde_json::json!({
+            "runs": runs,
+            "failed_runs": failed_runs,
+            "total_records": total_records,
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else if !quiet {
+        println!(
+            "\nStopped after {} syncs ({} failed), {} records synced",
+            runs, failed_runs, total_records
+        );
+    }
+    Ok(())
+}
+
+/// Run one incremental sync, connecting first if needed.
+///
+/// A client kept from an earlier pass is health-checked first; if either
+/// backend is down the sync is skipped. The client is dropped on error so the
+/// next pass reconnects.
+async fn watch_pass(
+    config: &SyncConfig,
+    metrics: &Arc<Metrics>,
+    client: &mut Option<SyncClient>,
+    cancel: CancellationToken,
+) -> Result<SyncResult, motherduck_supasync::Error> {
+    let sync_client = match client {
+        Some(c) => {
+            let health = c.health().await;
+            if !health.ok {
+                *client = None;
+                return Err(motherduck_supasync::Error::sync(
+                    format!("Skipped, health check failed: {}", health),
+                    0,
+                ));
+            }
+            c
+        }
+        None => client.insert(SyncClient::new_with_metrics(config.clone(), metrics.clone()).await?),
+    };
+
+    let result = sync_client
+        .sync_with_cancel(SyncMode::Incremental, cancel)
+        .await;
+    match result {
+        Ok(_)
+        | Err(motherduck_supasync::Error::Cancelled { .. })
+        | Err(motherduck_supasync::Error::Aborted { .. }) => {}
+        Err(_) => *client = None,
+    }
+    result
+}
+
+/// Render sync progress as one bar per table.
+///
+/// Tables whose row count isn't known up front (streaming) get a spinner
+/// counting rows instead of a bar.
+fn progress_bars() -> impl Fn(motherduck_supasync::SyncProgress) + Send + Sync + 'static {
+    let multi = MultiProgress::new();
+    let bars: Mutex<HashMap<String, ProgressBar>> = Mutex::default();
+    let counting = ProgressStyle::with_template("{spinner} {prefix:.bold} {human_pos} rows  {msg}")
+        .expect("valid progress template");
+    let sized = ProgressStyle::with_template(
+        "{spinner} {prefix:.bold} [{bar:30.cyan/blue}] {human_pos}/{human_len}  {msg}",
+    )
+    .expect("valid progress template")
+    .progress_chars("=> ");
+
+    move |progress| {
+        let mut bars = bars.lock().unwrap_or_else(|e| e.into_inner());
+        let bar = bars.entry(progress.table.clone()).or_insert_with(|| {
+            let bar = multi.add(ProgressBar::no_length().with_style(counting.clone()));
+            bar.set_prefix(progress.table.clone());
+            bar.enable_steady_tick(Duration::from_millis(120));
+            bar
+        });
+
+        if let Some(total) = progress.total_records {
+            if bar.length() != Some(total as u64) {
+                bar.set_style(sized.clone());
+                bar.set_length(total as u64);
+            }
+        }
+        // A failure carries no counts; keep the bar where it stopped
+        if progress.phase != SyncPhase::Failed {
+            bar.set_position(progress.records_processed as u64);
+        }
+
+        let mut message = progress.phase.to_string();
+        if let Some(rate) = progress.rows_per_second {
+            message.push_str(&format!(" · {:.0} rows/s", rate));
+        }
+        if let Some(eta) = progress.eta {
+            message.push_str(&format!(" · {} left", HumanDuration(eta)));
+        }
+
+        match progress.phase {
+            SyncPhase::Completed | SyncPhase::Failed => bar.finish_with_message(message),
+            _ => bar.set_message(message),
+        }
+    }
+}
+
+/// Serve `/metrics` in the background for the lifetime of the process.
+#[cfg(feature = "prometheus")]
+fn serve_metrics(
+    metrics: Arc<Metrics>,
+    addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error>> {
+    tokio::spawn(async move {
+        if let Err(e) = motherduck_supasync::metrics::serve_prometheus(metrics, addr).await {
+            error!("Metrics endpoint on {} stopped: {}", addr, e);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(feature = "prometheus"))]
+fn serve_metrics(
+    _metrics: Arc<Metrics>,
+    _addr: SocketAddr,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Err("--metrics-addr requires building with the `prometheus` feature".into())
+}
+
+/// Wait `interval`, doubling per consecutive failure up to [`MAX_WATCH_BACKOFF`].
+fn watch_delay(interval: Duration, consecutive_failures: u32) -> Duration {
+    let backoff = interval.saturating_mul(1 << consecutive_failures.min(16));
+    backoff.min(MAX_WATCH_BACKOFF.max(interval))
+}
+
+/// Resolve on Ctrl-C or (on Unix) SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{SignalKind, signal};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+}
+
+async fn run_validate(
+    config: SyncConfig,
+    json: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use motherduck_supasync::postgres::PostgresClient;
+
+    config.validate()?;
+
+    let pg_client = PostgresClient::connect(config.postgres.clone()).await?;
+    let mut problems: std::collections::BTreeMap<String, Vec<String>> =
+        std::collections::BTreeMap::new();
+
+    for mapping in config.tables.iter().filter(|t| t.enabled) {
+        let table_problems = match pg_client.introspect_source(mapping).await {
+            Ok(columns) => {
+                let available: Vec<String> = columns.into_iter().map(|c| c.name).collect();
+                mapping.check_source_columns(&available)
+            }
+            // A broken source query is a problem with this mapping, not with the run
+            Err(e) if mapping.source_query.is_some() => {
+                vec![format!("source query failed: {}", e)]
+            }
+            Err(e) => return Err(e.into()),
+        };
+        problems.insert(mapping.source_table.clone(), table_problems);
+    }
+
+    let failed = problems.values().filter(|p| !p.is_empty()).count();
+
+    if json {
+        let summary = serde_json::json!({
+            "valid": failed == 0,
+            "tables": problems,
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else if !quiet {
+        println!("Validating {} enabled tables\n", problems.len());
+        for (table, table_problems) in &problems {
+            if table_problems.is_empty() {
+                println!("  ✓ {}", table);
+            } else {
+                println!("  ✗ {}", table);
+                for problem in table_problems {
+                    println!("      {}", problem);
+                }
+            }
+        }
+    }
+
+    if failed == 0 {
+        if !quiet && !json {
+            println!("\n✓ Config is valid");
+        }
+        Ok(())
+    } else {
+        Err(format!("{} of {} tables misconfigured", failed, problems.len()).into())
+    }
+}
+
+/// Row counts for one table in `diff`.
+#[derive(serde::Serialize)]
+struct TableDiff {
+    target: String,
+    source_rows: i64,
+    target_rows: i64,
+    delta: i64,
+    /// Source rows still waiting on the sync flag (flag tables only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    unsynced: Option<i64>,
+    drifted: bool,
+}
+
+async fn run_diff(
+    config: SyncConfig,
+    tolerance: u64,
+    json: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use motherduck_supasync::MotherDuckClient;
+    use motherduck_supasync::postgres::PostgresClient;
+
+    let pg_client = PostgresClient::connect(config.postgres.clone()).await?;
+    let md_client = MotherDuckClient::connect(config.motherduck.clone())?;
+
+    let mut diffs = std::collections::BTreeMap::new();
+    for mapping in config.tables.iter().filter(|t| t.enabled) {
+        let source_rows = pg_client.count_source_rows(mapping).await?;
+        // A target that doesn't exist yet holds no rows
+        let target_rows = if md_client.table_exists(&mapping.target_table)? {
+            md_client.count_rows(&mapping.target_table)?
+        } else {
+            0
+        };
+        let unsynced = match mapping.watermark_column {
+            None if config.sync.mark_synced => Some(pg_client.unsynced_count(mapping, None).await?),
+            _ => None,
+        };
+
+        let delta = target_rows - source_rows;
+        diffs.insert(
+            mapping.source_table.clone(),
+            TableDiff {
+                target: mapping.target_table.clone(),
+                source_rows,
+                target_rows,
+                delta,
+                unsynced,
+                drifted: delta.unsigned_abs() > tolerance,
+            },
+        );
+    }
+
+    let drifted = diffs.values().filter(|d| d.drifted).count();
+
+    if json {
+        let summary = serde_json::json!({
+            "tolerance": tolerance,
+            "drifted": drifted,
+            "tables": diffs,
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else if !quiet {
+        println!("Row counts (PostgreSQL → MotherDuck)\n");
+        for (table, diff) in &diffs {
+            let icon = if diff.drifted { "✗" } else { "✓" };
+            print!(
+                "  {} {} → {}: {} → {} ({:+})",
+                icon, table, diff.target, diff.source_rows, diff.target_rows, diff.delta
+            );
+            match diff.unsynced {
+                Some(unsynced) => println!(", {} unsynced", unsynced),
+                None => println!(),
+            }
+        }
+    }
+
+    if drifted == 0 {
+        if !quiet && !json {
+            println!("\n✓ All tables within {} rows", tolerance);
+        }
+        Ok(())
+    } else {
+        Err(format!(
+            "{} of {} tables differ by more than {} rows",
+            drifted,
+            diffs.len(),
+            tolerance
+        )
+        .into())
+    }
+}
+
+fn run_diff_runs(
+    previous: &str,
+    current: &str,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let load = |path: &str| -> Result<SyncResult, Box<dyn std::error::Error>> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("{} is not a sync result: {}", path, e).into())
+    };
+    let delta = load(current)?.delta(&load(previous)?);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&delta)?);
+        return Ok(());
+    }
+
+    println!("Changes from {} to {}\n", previous, current);
+    for (table, change) in &delta.tables {
+        let mark = if delta.newly_failing.contains(table) {
+            " (newly failing)"
+        } else if delta.recovered.contains(table) {
+            " (recovered)"
+        } else if change.previous_duration_ms.is_none() {
+            " (new)"
+        } else {
+            ""
+        };
+        println!(
+            "  {}: {:+} records, {:+}ms{}",
+            table, change.records_synced, change.duration_ms, mark
+        );
+    }
+    println!(
+        "\nTotal: {:+} records, {:+}ms",
+        delta.records_synced, delta.duration_ms
+    );
+    Ok(())
+}
+
 async fn run_test(config: SyncConfig, json: bool) -> Result<(), Box<dyn std::error::Error>> {
     if !json {
         println!("Testing connectivity...\n");
     }
 
     let client = SyncClient::new(config).await?;
-    client.test_connectivity().await?;
+    let health = client.health().await;
 
     if json {
-        println!(r#"{{"postgres":"ok","motherduck":"ok"}}"#);
+        println!("{}", serde_json::to_string_pretty(&health)?);
     } else {
-        println!("\n✓ All connectivity tests passed!");
+        println!("  PostgreSQL: {}", health.postgres);
+        println!("  MotherDuck: {}", health.motherduck);
+    }
+
+    if health.ok {
+        if !json {
+            println!("\n✓ All connectivity tests passed!");
+        }
+        Ok(())
+    } else {
+        Err("Connectivity test failed".into())
     }
-    Ok(())
 }
 
 async fn run_status(config: SyncConfig, json: bool) -> Result<(), Box<dyn std::error::Error>> {
@@ -225,24 +945,39 @@ async fn run_status(config: SyncConfig, json: bool) -> Result<(), Box<dyn std::e
     Ok(())
 }
 
+/// Output format of `query --sql`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum QueryFormat {
+    /// Aligned text table
+    Table,
+    /// JSON array of row objects
+    Json,
+    /// RFC 4180 CSV with a header row
+    Csv,
+}
+
 async fn run_query(
     config: SyncConfig,
     sql: Option<String>,
     counts: bool,
     tables: bool,
-    json: bool,
+    format: QueryFormat,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use motherduck_supasync::MotherDuckClient;
 
+    let json = format == QueryFormat::Json;
+
+    let target_tables = enabled_targets(&config);
+    let schema = config.motherduck.schema.clone();
     let md_client = MotherDuckClient::connect(config.motherduck)?;
 
     // List tables
     if tables {
-        let query = "SELECT table_name FROM information_schema.tables WHERE table_schema = 'main' ORDER BY table_name";
+        let query = "SELECT table_name FROM information_schema.tables WHERE table_catalog = current_database() AND table_schema = ? ORDER BY table_name";
         let conn = md_client.connection();
         let mut stmt = conn.prepare(query)?;
         let table_names: Vec<String> = stmt
-            .query_map([], |row| row.get(0))?
+            .query_map([&schema], |row| row.get(0))?
             .filter_map(|r| r.ok())
             .collect();
 
@@ -260,11 +995,6 @@ async fn run_query(
 
     // Show counts for all tables
     if counts {
-        let target_tables = [
-            "daily_stats",
-            "user_activity_summary",
-            "post_activity_daily_stats",
-        ];
         let mut results: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
 
         for table in &target_tables {
@@ -299,80 +1029,66 @@ async fn run_query(
     if let Some(sql_query) = sql {
         let conn = md_client.connection();
 
-        // Use query() directly and iterate
         let mut stmt = conn.prepare(&sql_query)?;
-        let mut rows_result = stmt.query([])?;
-
-        let mut rows: Vec<Vec<String>> = Vec::new();
-        let mut column_count = 0;
-
-        while let Some(row) = rows_result.next()? {
-            if column_count == 0 {
-                // Detect column count from first row by trying to access columns
-                for i in 0..100 {
-                    match row.get::<_, duckdb::types::Value>(i) {
-                        Ok(_) => column_count = i + 1,
-                        Err(_) => break,
-                    }
-                }
-            }
-
-            let mut values = Vec::new();
-            for i in 0..column_count {
-                let val: String = match row.get::<_, duckdb::types::Value>(i) {
-                    Ok(v) => match v {
-                        duckdb::types::Value::Null => "NULL".to_string(),
-                        duckdb::types::Value::Text(s) => s,
-                        duckdb::types::Value::Int(n) => n.to_string(),
-                        duckdb::types::Value::BigInt(n) => n.to_string(),
-                        duckdb::types::Value::Float(n) => n.to_string(),
-                        duckdb::types::Value::Double(n) => n.to_string(),
-                        duckdb::types::Value::Boolean(b) => b.to_string(),
-                        duckdb::types::Value::Date32(d) => format!("{}", d),
-                        duckdb::types::Value::Timestamp(_, t) => format!("{}", t),
-                        other => format!("{:?}", other),
-                    },
-                    Err(_) => "?".to_string(),
-                };
-                values.push(val);
+        let mut result = stmt.query([])?;
+        let column_names = result
+            .as_ref()
+            .map(|stmt| stmt.column_names())
+            .unwrap_or_default();
+
+        let mut rows: Vec<Vec<serde_json::Value>> = Vec::new();
+        while let Some(row) = result.next()? {
+            let mut values = Vec::with_capacity(column_names.len());
+            for i in 0..column_names.len() {
+                values.push(duck_value_to_json(row.get(i)?));
             }
             rows.push(values);
         }
 
-        // Generate column names
-        let column_names: Vec<String> = (0..column_count).map(|i| format!("col{}", i)).collect();
-
         if json {
             let json_rows: Vec<serde_json::Value> = rows
-                .iter()
+                .into_iter()
                 .map(|row| {
-                    let mut obj = serde_json::Map::new();
-                    for (i, col) in column_names.iter().enumerate() {
-                        if i < row.len() {
-                            obj.insert(col.clone(), serde_json::Value::String(row[i].clone()));
-                        }
-                    }
-                    serde_json::Value::Object(obj)
+                    serde_json::Value::Object(column_names.iter().cloned().zip(row).collect())
                 })
                 .collect();
             println!("{}", serde_json::to_string_pretty(&json_rows)?);
-        } else {
-            if column_names.is_empty() {
-                println!("Query executed successfully (no results)");
-            } else {
-                // Print header
-                println!("{}", column_names.join(" | "));
-                println!(
+        } else if format == QueryFormat::Csv {
+            print!("{}", csv_line(column_names.iter().cloned()));
+            for row in rows {
+                // NULLs are empty fields
+                print!(
                     "{}",
-                    "-".repeat(column_names.iter().map(|c| c.len() + 3).sum::<usize>())
+                    csv_line(row.into_iter().map(|v| match v {
+                        serde_json::Value::Null => String::new(),
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    }))
                 );
+            }
+        } else if column_names.is_empty() {
+            println!("Query executed successfully (no results)");
+        } else {
+            // Print header
+            println!("{}", column_names.join(" | "));
+            println!(
+                "{}",
+                "-".repeat(column_names.iter().map(|c| c.len() + 3).sum::<usize>())
+            );
 
-                // Print rows
-                for row in &rows {
-                    println!("{}", row.join(" | "));
-                }
-                println!("\n{} rows returned", rows.len());
+            // Print rows
+            for row in &rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|v| match v {
+                        serde_json::Value::Null => "NULL".to_string(),
+                        serde_json::Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    })
+                    .collect();
+                println!("{}", cells.join(" | "));
             }
+            println!("\n{} rows returned", rows.len());
         }
         return Ok(());
     }
@@ -383,31 +1099,159 @@ async fn run_query(
     println!("  motherduck-supasync query --tables      # List all tables");
     println!("  motherduck-supasync query --counts      # Show row counts");
     println!("  motherduck-supasync query --sql \"SELECT * FROM daily_stats LIMIT 5\"");
+    println!("  motherduck-supasync query --sql \"SELECT * FROM daily_stats\" --format csv");
     Ok(())
 }
 
-async fn run_clean(
+/// One CSV record terminated by CRLF, quoting fields that hold a comma, quote
+/// or line break (RFC 4180).
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    let mut line = fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Convert a DuckDB value into JSON, keeping numbers and booleans typed.
+fn duck_value_to_json(value: duckdb::types::Value) -> serde_json::Value {
+    use duckdb::types::{TimeUnit, Value};
+    use serde_json::Value as Json;
+
+    let to_micros = |unit: TimeUnit, v: i64| match unit {
+        TimeUnit::Second => v.saturating_mul(1_000_000),
+        TimeUnit::Millisecond => v.saturating_mul(1_000),
+        TimeUnit::Microsecond => v,
+        TimeUnit::Nanosecond => v / 1_000,
+    };
+    let float = |v: f64| serde_json::Number::from_f64(v).map_or(Json::Null, Json::Number);
+
+    match value {
+        Value::Null => Json::Null,
+        Value::Boolean(b) => Json::Bool(b),
+        Value::TinyInt(n) => n.into(),
+        Value::SmallInt(n) => n.into(),
+        Value::Int(n) => n.into(),
+        Value::BigInt(n) => n.into(),
+        Value::UTinyInt(n) => n.into(),
+        Value::USmallInt(n) => n.into(),
+        Value::UInt(n) => n.into(),
+        Value::UBigInt(n) => n.into(),
+        Value::HugeInt(n) => {
+            i64::try_from(n).map_or_else(|_| Json::String(n.to_string()), Json::from)
+        }
+        Value::Float(n) => float(n as f64),
+        Value::Double(n) => float(n),
+        Value::Decimal(d) => d.to_string().parse::<f64>().map_or(Json::Null, float),
+        Value::Text(s) | Value::Enum(s) => Json::String(s),
+        Value::Date32(days) => chrono::NaiveDate::from_num_days_from_ce_opt(days + 719_163)
+            .map_or(Json::Null, |d| Json::String(d.to_string())),
+        Value::Timestamp(unit, v) => chrono::DateTime::from_timestamp_micros(to_micros(unit, v))
+            .map_or(Json::Null, |t| Json::String(t.naive_utc().to_string())),
+        Value::Time64(unit, v) => {
+            let micros = to_micros(unit, v);
+            chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+                (micros / 1_000_000) as u32,
+                (micros % 1_000_000) as u32 * 1_000,
+            )
+            .map_or(Json::Null, |t| Json::String(t.to_string()))
+        }
+        Value::List(items) | Value::Array(items) => {
+            Json::Array(items.into_iter().map(duck_value_to_json).collect())
+        }
+        Value::Struct(fields) => Json::Object(
+            fields
+                .iter()
+                .map(|(k, v)| (k.clone(), duck_value_to_json(v.clone())))
+                .collect(),
+        ),
+        Value::Union(inner) => duck_value_to_json(*inner),
+        other => Json::String(format!("{:?}", other)),
+    }
+}
+
+fn run_export(
     config: SyncConfig,
+    table: Option<String>,
+    sql: Option<String>,
+    format: ExportFormat,
+    out: &str,
+    json: bool,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use motherduck_supasync::MotherDuckClient;
+
+    let md_client = MotherDuckClient::connect(config.motherduck)?;
+    let (source, rows) = match (table, sql) {
+        (Some(table), _) => {
+            let rows = md_client.export_table(&table, format, out)?;
+            (table, rows)
+        }
+        (None, Some(sql)) => {
+            let rows = md_client.export_query(&sql, format, out)?;
+            ("query".to_string(), rows)
+        }
+        (None, None) => return Err("Either --table or --sql is required".into()),
+    };
+
+    if json {
+        let summary = serde_json::json!({
+            "source": source,
+            "format": format.to_string(),
+            "path": out,
+            "rows": rows,
+        });
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else if !quiet {
+        println!(
+            "✓ Exported {} rows from {} to {} ({})",
+            rows, source, out, format
+        );
+    }
+    Ok(())
+}
+
+/// Flags of the `clean` command.
+struct CleanOptions {
     reset: bool,
     truncate: bool,
     table: Option<String>,
+    all: bool,
+    yes: bool,
+}
+
+async fn run_clean(
+    config: SyncConfig,
+    options: CleanOptions,
     json: bool,
     quiet: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use motherduck_supasync::MotherDuckClient;
 
+    let CleanOptions {
+        reset,
+        truncate,
+        table,
+        all,
+        yes,
+    } = options;
+    let target_tables = enabled_targets(&config);
     let md_client = MotherDuckClient::connect(config.motherduck)?;
-    let target_tables = [
-        "daily_stats",
-        "user_activity_summary",
-        "post_activity_daily_stats",
-    ];
 
     // Determine which tables to clean
-    let tables_to_clean: Vec<&str> = if let Some(ref t) = table {
-        vec![t.as_str()]
+    let tables_to_clean: Vec<String> = if let Some(t) = table {
+        vec![t]
+    } else if all {
+        md_client.list_tables()?
     } else {
-        target_tables.to_vec()
+        target_tables
     };
 
     if !quiet && !json {
@@ -425,17 +1269,32 @@ async fn run_clean(
             println!(
                 "  motherduck-supasync clean --truncate -t daily_stats  # Truncate specific table"
             );
+            println!(
+                "  motherduck-supasync clean --truncate --all     # Every table in the schema"
+            );
             return Ok(());
         }
     }
 
+    if (reset || truncate) && !tables_to_clean.is_empty() && !yes {
+        let action = if reset { "Drop" } else { "Truncate" };
+        let prompt = format!(
+            "{} {} table(s): {}?",
+            action,
+            tables_to_clean.len(),
+            tables_to_clean.join(", ")
+        );
+        if !confirm(&prompt)? {
+            return Err("Clean cancelled".into());
+        }
+    }
+
     let mut results: std::collections::HashMap<String, String> = std::collections::HashMap::new();
 
     for table_name in &tables_to_clean {
         if reset {
             // Drop and recreate
-            let drop_sql = format!("DROP TABLE IF EXISTS {}", table_name);
-            match md_client.execute(&drop_sql) {
+            match md_client.drop_table(table_name) {
                 Ok(_) => {
                     if !quiet && !json {
                         println!("  ✓ Dropped: {}", table_name);
@@ -451,8 +1310,7 @@ async fn run_clean(
             }
         } else if truncate {
             // Truncate (DELETE all rows)
-            let truncate_sql = format!("DELETE FROM {}", table_name);
-            match md_client.execute(&truncate_sql) {
+            match md_client.truncate_table(table_name) {
                 Ok(count) => {
                     if !quiet && !json {
                         println!("  ✓ Truncated: {} ({} rows deleted)", table_name, count);
@@ -469,12 +1327,12 @@ async fn run_clean(
         }
     }
 
-    // Recreate tables if reset
-    if reset {
+    // Recreate analytics tables if reset; synced tables are recreated by the next sync
+    if reset && !config.analytics_tables.is_empty() {
         if !quiet && !json {
-            println!("\nRecreating tables...");
+            println!("\nRecreating analytics tables...");
         }
-        md_client.create_analytics_tables()?;
+        md_client.create_analytics_tables(&config.analytics_tables)?;
         if !quiet && !json {
             println!("  ✓ Tables recreated");
         }
@@ -489,6 +1347,139 @@ async fn run_clean(
     Ok(())
 }
 
+/// Target tables of all enabled mappings.
+fn enabled_targets(config: &SyncConfig) -> Vec<String> {
+    config
+        .tables
+        .iter()
+        .filter(|t| t.enabled)
+        .map(|t| t.target_table.clone())
+        .collect()
+}
+
+/// Ask a yes/no question on stderr, defaulting to no.
+///
+/// Without a terminal to ask on, the answer is no; pass `--yes` instead.
+fn confirm(prompt: &str) -> std::io::Result<bool> {
+    use std::io::{BufRead, Write};
+
+    if !std::io::stdin().is_terminal() {
+        eprintln!("{} Not a terminal; pass --yes to confirm", prompt);
+        return Ok(false);
+    }
+    eprint!("{} [y/N] ", prompt);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "YES"))
+}
+
+/// Output format of `discover`.
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum DiscoverFormat {
+    /// `[[tables]]` blocks for the TOML config
+    Toml,
+    /// JSON array for tables.local.json / SYNC_TABLES_CONFIG
+    Json,
+}
+
+async fn run_discover(
+    config: SyncConfig,
+    schema: &str,
+    format: DiscoverFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use motherduck_supasync::postgres::PostgresClient;
+
+    let pg_client = PostgresClient::connect(config.postgres.clone()).await?;
+    let sync_flag = &config.sync.sync_flag_column;
+    let tables = pg_client.discover_tables(schema, sync_flag).await?;
+    if tables.is_empty() {
+        warn!("No base tables found in schema {}", schema);
+    }
+
+    match format {
+        DiscoverFormat::Toml => print!("{}", discovered_toml(&tables, sync_flag)),
+        DiscoverFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&discovered_json(&tables))?
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Render discovered tables as `[[tables]]` blocks.
+///
+/// Tables without a primary key are written commented out, and tables without
+/// the sync flag column get a note on how to sync them.
+fn discovered_toml(
+    tables: &[motherduck_supasync::postgres::DiscoveredTable],
+    sync_flag: &str,
+) -> String {
+    let mut out = String::new();
+    for table in tables {
+        let target = table.name.rsplit('.').next().unwrap_or(&table.name);
+        let primary_key: Vec<String> = table
+            .primary_key
+            .iter()
+            .map(|c| format!("{:?}", c))
+            .collect();
+        let mut block = format!(
+            "[[tables]]\nsource_table = {:?}\ntarget_table = {:?}\nprimary_key = [{}]\n",
+            table.name,
+            target,
+            primary_key.join(", ")
+        );
+
+        if !table.has_sync_flag {
+            out.push_str(&format!(
+                "# {} has no boolean {} column: add one (see docs/database-setup.md)\n# or set watermark_column to a column that increases on every change\n",
+                table.name, sync_flag
+            ));
+        }
+        if table.primary_key.is_empty() {
+            out.push_str(&format!(
+                "# {} has no primary key: set primary_key to a unique column\n",
+                table.name
+            ));
+            block = block.lines().map(|line| format!("# {}\n", line)).collect();
+        }
+        out.push_str(&block);
+        out.push('\n');
+    }
+    out
+}
+
+/// Render discovered tables in the `tables.local.json` format.
+///
+/// JSON has no comments, so tables without a primary key are left out with a warning.
+fn discovered_json(
+    tables: &[motherduck_supasync::postgres::DiscoveredTable],
+) -> Vec<serde_json::Value> {
+    tables
+        .iter()
+        .filter(|table| {
+            if table.primary_key.is_empty() {
+                warn!("Skipping {}: no primary key", table.name);
+            } else if !table.has_sync_flag {
+                warn!(
+                    "{} has no sync flag column; add one or set watermark_column",
+                    table.name
+                );
+            }
+            !table.primary_key.is_empty()
+        })
+        .map(|table| {
+            serde_json::json!({
+                "source": table.name,
+                "target": table.name.rsplit('.').next().unwrap_or(&table.name),
+                "pk": table.primary_key,
+            })
+        })
+        .collect()
+}
+
 fn run_generate_secret(input: &str) -> Result<(), Box<dyn std::error::Error>> {
     use base64::{Engine, engine::general_purpose::STANDARD};
 
@@ -530,6 +1521,11 @@ database = "analytics"
 batch_size = 1000
 mark_synced = true
 
+# Optional MotherDuck-only tables (aggregates etc.), created before each sync
+# [analytics_tables]
+# ddl = ["CREATE TABLE IF NOT EXISTS events (id VARCHAR PRIMARY KEY, name VARCHAR)"]
+# sql_file = "analytics_tables.sql"
+
 [[tables]]
 source_table = "analytics_daily_stats"
 target_table = "daily_stats"
@@ -556,20 +1552,100 @@ enabled = true
     Ok(())
 }
 
-fn init_logging(level: &str, quiet: bool, json_output: bool) {
-    if quiet {
-        return;
+type FmtLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Subscriber with the log formatter applied, which the OTLP layer sits on.
+#[cfg(feature = "otel")]
+type FmtSubscriber =
+    tracing_subscriber::layer::Layered<reload::Layer<Option<FmtLayer>, Registry>, Registry>;
+
+/// Slot for the OTLP layer, filled in once the config is loaded.
+#[cfg(feature = "otel")]
+type OtelHandle =
+    reload::Handle<Option<motherduck_supasync::telemetry::OtelLayer<FmtSubscriber>>, FmtSubscriber>;
+
+#[cfg(not(feature = "otel"))]
+struct OtelHandle;
+
+/// Parts of the subscriber that the config can change after logging starts.
+struct LogHandles {
+    /// Log formatter (`None` when quiet), switched to JSON by `logging.format`
+    format: reload::Handle<Option<FmtLayer>, Registry>,
+    otel: OtelHandle,
+}
+
+/// Log formatter writing text or JSON lines.
+fn fmt_layer(format: LogFormat, json_output: bool) -> FmtLayer {
+    // When JSON output is enabled, send logs to stderr to avoid mixing with JSON on stdout
+    let writer = if json_output {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+    let layer = fmt::layer().with_target(false).with_writer(writer);
+    match format {
+        LogFormat::Text => layer.boxed(),
+        LogFormat::Json => layer.json().boxed(),
     }
+}
+
+/// Start logging before the config is loaded; `--json` selects JSON log lines.
+fn init_logging(level: &str, quiet: bool, json_output: bool) -> LogHandles {
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level));
 
-    // When JSON output is enabled, send logs to stderr to avoid mixing with JSON on stdout
-    if json_output {
-        fmt()
-            .with_env_filter(filter)
-            .with_target(false)
-            .with_writer(std::io::stderr)
-            .init();
+    let format = if json_output {
+        LogFormat::Json
     } else {
-        fmt().with_env_filter(filter).with_target(false).init();
+        LogFormat::Text
+    };
+    let (fmt, format) = reload::Layer::new((!quiet).then(|| fmt_layer(format, json_output)));
+
+    #[cfg(feature = "otel")]
+    {
+        let (otel, handle) = reload::Layer::new(None);
+        tracing_subscriber::registry()
+            .with(fmt)
+            .with(otel)
+            .with(filter)
+            .init();
+        LogHandles {
+            format,
+            otel: handle,
+        }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        tracing_subscriber::registry().with(fmt).with(filter).init();
+        LogHandles {
+            format,
+            otel: OtelHandle,
+        }
+    }
+}
+
+/// Start exporting spans to `endpoint`, if configured.
+#[cfg(feature = "otel")]
+fn start_otel(
+    handle: OtelHandle,
+    endpoint: Option<&str>,
+) -> Result<Option<motherduck_supasync::telemetry::OtelGuard>, Box<dyn std::error::Error>> {
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+    let (layer, guard) = motherduck_supasync::telemetry::otlp_layer(endpoint)?;
+    handle.reload(Some(layer))?;
+    info!("Exporting traces to {}", endpoint);
+    Ok(Some(guard))
+}
+
+#[cfg(not(feature = "otel"))]
+fn start_otel(
+    _handle: OtelHandle,
+    endpoint: Option<&str>,
+) -> Result<Option<()>, Box<dyn std::error::Error>> {
+    if endpoint.is_some() {
+        warn!("logging.otlp_endpoint is ignored: built without the `otel` feature");
     }
+    Ok(None)
 }
@@ -0,0 +1,169 @@
+//! Resumable sync checkpoints.
+//!
+//! With `sync.checkpoint_path` set, every table that finishes successfully is
+//! recorded in a JSON file. A sync that dies halfway leaves the file behind, and
+//! the next sync in the same mode resumes that run, skipping the tables already
+//! done. The file is removed once a run completes without failures.
+
+use crate::error::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Progress of one sync run, persisted after each completed table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Identifies the run being resumed
+    pub run_id: String,
+    /// Sync mode of the run (`full` or `incremental`)
+    pub mode: String,
+    /// When the run started
+    pub started_at: DateTime<Utc>,
+    /// Completed tables, keyed by source table
+    #[serde(default)]
+    pub tables: BTreeMap<String, TableCheckpoint>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+/// A table completed within a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableCheckpoint {
+    /// When the table finished
+    pub completed_at: DateTime<Utc>,
+    /// Records synced
+    pub records_synced: usize,
+    /// Stored watermark after the table finished, for watermark tables
+    #[serde(default)]
+    pub watermark: Option<JsonValue>,
+}
+
+impl Checkpoint {
+    /// Resume the run recorded at `path` if it was in `mode`, otherwise start a new one.
+    ///
+    /// A checkpoint left by a run in another mode is discarded, since e.g. an
+    /// incremental run says nothing about which tables a full run still needs.
+    pub fn resume_or_start(path: impl AsRef<Path>, mode: &str) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(mut checkpoint) = Self::load(path)? {
+            if checkpoint.mode == mode {
+                info!(
+                    "Resuming {} sync run {} ({} tables already done)",
+                    mode,
+                    checkpoint.run_id,
+                    checkpoint.tables.len()
+                );
+                checkpoint.path = path.to_path_buf();
+                return Ok(checkpoint);
+            }
+            info!(
+                "Discarding checkpoint for {} run {}",
+                checkpoint.mode, checkpoint.run_id
+            );
+        }
+
+        Ok(Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            mode: mode.to_string(),
+            started_at: Utc::now(),
+            tables: BTreeMap::new(),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Read the checkpoint at `path`, if there is one.
+    pub fn load(path: impl AsRef<Path>) -> Result<Option<Self>> {
+        let path = path.as_ref();
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| Error::Serialization {
+                message: format!("Invalid checkpoint file {}", path.display()),
+                source: Some(Box::new(e)),
+            })
+    }
+
+    /// Delete the checkpoint at `path`. Missing files are fine.
+    pub fn clear(path: impl AsRef<Path>) -> Result<()> {
+        match std::fs::remove_file(path.as_ref()) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `table` already completed in this run.
+    pub fn is_complete(&self, table: &str) -> bool {
+        self.tables.contains_key(table)
+    }
+
+    /// Record `table` as complete and write the checkpoint.
+    pub fn complete(
+        &mut self,
+        table: &str,
+        records_synced: usize,
+        watermark: Option<JsonValue>,
+    ) -> Result<()> {
+        self.tables.insert(
+            table.to_string(),
+            TableCheckpoint {
+                completed_at: Utc::now(),
+                records_synced,
+                watermark,
+            },
+        );
+        self.save()
+    }
+
+    /// Write the checkpoint, replacing the previous file atomically.
+    fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| Error::Serialization {
+            message: "Failed to serialize checkpoint".into(),
+            source: Some(Box::new(e)),
+        })?;
+
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, &self.path)?;
+        debug!("Saved checkpoint to {}", self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.json");
+
+        let mut checkpoint = Checkpoint::resume_or_start(&path, "full").unwrap();
+        checkpoint
+            .complete("users", 10, Some(serde_json::json!("2024-01-01")))
+            .unwrap();
+
+        let resumed = Checkpoint::resume_or_start(&path, "full").unwrap();
+        assert_eq!(resumed.run_id, checkpoint.run_id);
+        assert!(resumed.is_complete("users"));
+        assert!(!resumed.is_complete("posts"));
+
+        // A different mode starts over
+        let fresh = Checkpoint::resume_or_start(&path, "incremental").unwrap();
+        assert_ne!(fresh.run_id, checkpoint.run_id);
+        assert!(fresh.tables.is_empty());
+
+        Checkpoint::clear(&path).unwrap();
+        assert!(Checkpoint::load(&path).unwrap().is_none());
+        Checkpoint::clear(&path).unwrap();
+    }
+}
@@ -47,26 +47,59 @@
 //!     .sync_flag_column("synced")
 //!     .build();
 //! ```
+//!
+//! ## Table Hooks
+//!
+//! Run your own MotherDuck SQL around each table:
+//!
+//! ```rust,no_run
+//! use motherduck_supasync::{SyncClient, SyncConfig};
+//!
+//! # async fn run(config: SyncConfig) -> motherduck_supasync::Result<()> {
+//! let client = SyncClient::new(config)
+//!     .await?
+//!     .with_table_hook(|mapping, result, md| {
+//!         if mapping.target_table == "full_listings" && result.success && !result.dry_run {
+//!             md.execute("CREATE OR REPLACE TABLE listing_stats AS SELECT count(*) AS n FROM full_listings")?;
+//!         }
+//!         Ok(())
+//!     });
+//! # Ok(())
+//! # }
+//! ```
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
 #![deny(unsafe_code)]
 
+pub mod checkpoint;
 pub mod config;
 pub mod error;
 pub mod metrics;
 pub mod motherduck;
 pub mod postgres;
+pub mod report;
 pub mod schema;
+pub mod sink;
+pub mod source;
 pub mod sync;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+pub mod throttle;
 
 // Re-exports for convenience
 pub use config::{SyncConfig, SyncConfigBuilder, TableMapping, TableMappingBuilder};
 pub use error::{Error, Result};
-pub use motherduck::MotherDuckClient;
+pub use motherduck::{ExportFormat, MotherDuckClient, SchemaMismatch, StatementWatchdog};
 pub use schema::{Column, ColumnType, Schema};
-pub use sync::{SyncClient, SyncMode, SyncProgress, SyncResult};
+pub use sink::SyncSink;
+pub use source::{SourceCursor, SyncSource};
+pub use sync::{
+    BackendHealth, HealthReport, SyncClient, SyncDelta, SyncMode, SyncProgress, SyncResult,
+    TableDelta, TableSyncResult,
+};
+pub use tokio_util::sync::CancellationToken;
 
 /// Library version
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
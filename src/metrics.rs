@@ -1,7 +1,11 @@
 //! Metrics and observability for motherduck-supasync.
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
 
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
 use std::time::Instant;
 
 /// Metrics collector for sync operations.
@@ -25,6 +29,36 @@ pub struct Metrics {
     pub md_queries: AtomicU64,
     /// Retry count
     pub retries: AtomicU64,
+    /// Counters per source table
+    pub per_table: DashMap<String, TableMetrics>,
+}
+
+/// Metrics for a single table.
+#[derive(Debug, Default)]
+pub struct TableMetrics {
+    /// Records synced
+    pub records_synced: AtomicU64,
+    /// Records failed
+    pub records_failed: AtomicU64,
+    /// Total sync duration in milliseconds
+    pub duration_ms: AtomicU64,
+    /// Unix time of the last successful sync (0 = never)
+    pub last_success: AtomicI64,
+}
+
+impl TableMetrics {
+    /// Get snapshot of this table's metrics.
+    pub fn snapshot(&self) -> TableMetricsSnapshot {
+        let last_success = self.last_success.load(Ordering::Relaxed);
+        TableMetricsSnapshot {
+            records_synced: self.records_synced.load(Ordering::Relaxed),
+            records_failed: self.records_failed.load(Ordering::Relaxed),
+            duration_ms: self.duration_ms.load(Ordering::Relaxed),
+            last_success: (last_success > 0)
+                .then(|| DateTime::from_timestamp(last_success, 0))
+                .flatten(),
+        }
+    }
 }
 
 impl Metrics {
@@ -47,6 +81,26 @@ impl Metrics {
             .fetch_add(duration_ms, Ordering::Relaxed);
     }
 
+    /// Record the sync of one table.
+    pub fn record_table(
+        &self,
+        table: &str,
+        success: bool,
+        records: u64,
+        failed: u64,
+        duration_ms: u64,
+    ) {
+        let entry = self.per_table.entry(table.to_string()).or_default();
+        entry.records_synced.fetch_add(records, Ordering::Relaxed);
+        entry.records_failed.fetch_add(failed, Ordering::Relaxed);
+        entry.duration_ms.fetch_add(duration_ms, Ordering::Relaxed);
+        if success {
+            entry
+                .last_success
+                .store(Utc::now().timestamp(), Ordering::Relaxed);
+        }
+    }
+
     /// Record a PostgreSQL query.
     pub fn record_pg_query(&self) {
         self.pg_queries.fetch_add(1, Ordering::Relaxed);
@@ -74,6 +128,11 @@ impl Metrics {
             pg_queries: self.pg_queries.load(Ordering::Relaxed),
             md_queries: self.md_queries.load(Ordering::Relaxed),
             retries: self.retries.load(Ordering::Relaxed),
+            per_table: self
+                .per_table
+                .iter()
+                .map(|entry| (entry.key().clone(), entry.value().snapshot()))
+                .collect(),
         }
     }
 
@@ -88,6 +147,7 @@ impl Metrics {
         self.pg_queries.store(0, Ordering::Relaxed);
         self.md_queries.store(0, Ordering::Relaxed);
         self.retries.store(0, Ordering::Relaxed);
+        self.per_table.clear();
     }
 }
 
@@ -112,6 +172,21 @@ pub struct MetricsSnapshot {
     pub md_queries: u64,
     /// Retry count
     pub retries: u64,
+    /// Metrics per source table
+    pub per_table: BTreeMap<String, TableMetricsSnapshot>,
+}
+
+/// Snapshot of a single table's metrics.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TableMetricsSnapshot {
+    /// Records synced
+    pub records_synced: u64,
+    /// Records failed
+    pub records_failed: u64,
+    /// Total sync duration in milliseconds
+    pub duration_ms: u64,
+    /// Time of the last successful sync
+    pub last_success: Option<DateTime<Utc>>,
 }
 
 impl MetricsSnapshot {
@@ -141,6 +216,178 @@ impl MetricsSnapshot {
             (self.records_synced as f64 * 1000.0) / self.sync_duration_ms as f64
         }
     }
+
+    /// Render the snapshot in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let counters = [
+            ("supasync_syncs_total", "Syncs attempted", self.syncs_total),
+            (
+                "supasync_syncs_success_total",
+                "Successful syncs",
+                self.syncs_success,
+            ),
+            (
+                "supasync_syncs_failed_total",
+                "Failed syncs",
+                self.syncs_failed,
+            ),
+            (
+                "supasync_records_synced_total",
+                "Records synced",
+                self.records_synced,
+            ),
+            (
+                "supasync_records_failed_total",
+                "Records that failed to sync",
+                self.records_failed,
+            ),
+            (
+                "supasync_sync_duration_ms",
+                "Total time spent syncing in milliseconds",
+                self.sync_duration_ms,
+            ),
+            (
+                "supasync_pg_queries_total",
+                "PostgreSQL queries run",
+                self.pg_queries,
+            ),
+            (
+                "supasync_md_queries_total",
+                "MotherDuck queries run",
+                self.md_queries,
+            ),
+            ("supasync_retries_total", "Retried operations", self.retries),
+        ];
+        for (name, help, value) in counters {
+            write_metric(&mut out, name, "counter", help, value as f64);
+        }
+
+        let gauges = [
+            (
+                "supasync_success_rate",
+                "Share of syncs that succeeded",
+                self.success_rate(),
+            ),
+            (
+                "supasync_avg_sync_duration_ms",
+                "Average sync duration in milliseconds",
+                self.avg_sync_duration_ms(),
+            ),
+            (
+                "supasync_records_per_second",
+                "Records synced per second of sync time",
+                self.records_per_second(),
+            ),
+        ];
+        for (name, help, value) in gauges {
+            write_metric(&mut out, name, "gauge", help, value);
+        }
+
+        if !self.per_table.is_empty() {
+            self.write_table_metrics(&mut out);
+        }
+        out
+    }
+
+    /// Append the per-table metrics, one sample per table with a `table` label.
+    fn write_table_metrics(&self, out: &mut String) {
+        type Field = fn(&TableMetricsSnapshot) -> Option<f64>;
+        let families: [(&str, &str, &str, Field); 4] = [
+            (
+                "supasync_table_records_synced_total",
+                "counter",
+                "Records synced per table",
+                |t| Some(t.records_synced as f64),
+            ),
+            (
+                "supasync_table_records_failed_total",
+                "counter",
+                "Records that failed to sync per table",
+                |t| Some(t.records_failed as f64),
+            ),
+            (
+                "supasync_table_sync_duration_ms",
+                "counter",
+                "Total time spent syncing per table in milliseconds",
+                |t| Some(t.duration_ms as f64),
+            ),
+            (
+                "supasync_table_last_success_timestamp_seconds",
+                "gauge",
+                "Unix time of the last successful sync per table",
+                |t| t.last_success.map(|ts| ts.timestamp() as f64),
+            ),
+        ];
+
+        for (name, kind, help, field) in families {
+            let _ = writeln!(out, "# HELP {name} {help}");
+            let _ = writeln!(out, "# TYPE {name} {kind}");
+            for (table, metrics) in &self.per_table {
+                if let Some(value) = field(metrics) {
+                    let table = escape_label(table);
+                    let _ = writeln!(out, "{name}{{table=\"{table}\"}} {value}");
+                }
+            }
+        }
+    }
+}
+
+/// Escape a Prometheus label value.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Append one metric with its `# HELP` and `# TYPE` lines.
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: f64) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+    let _ = writeln!(out, "{name} {value}");
+}
+
+/// Serve `metrics` on `addr` at `/metrics` in the Prometheus text format.
+///
+/// Runs until the listener fails; spawn it next to the sync loop. Every other
+/// path answers 404.
+#[cfg(feature = "prometheus")]
+pub async fn serve_prometheus(
+    metrics: std::sync::Arc<Metrics>,
+    addr: std::net::SocketAddr,
+) -> crate::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    tracing::info!("Serving Prometheus metrics on http://{}/metrics", addr);
+
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 1024];
+            let n = match stream.read(&mut buf).await {
+                Ok(n) => n,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buf[..n]);
+            let path = request.split_whitespace().nth(1).unwrap_or("");
+
+            let (status, body) = if request.starts_with("GET ") && path == "/metrics" {
+                ("200 OK", metrics.snapshot().to_prometheus())
+            } else {
+                ("404 Not Found", String::from("Not Found\n"))
+            };
+            let response = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            if let Err(e) = stream.write_all(response.as_bytes()).await {
+                tracing::debug!("Failed to write metrics response: {}", e);
+            }
+        });
+    }
 }
 
 /// Timer for measuring operation duration.
@@ -208,10 +455,47 @@ mod tests {
             pg_queries: 20,
             md_queries: 30,
             retries: 3,
+            per_table: BTreeMap::new(),
         };
 
         assert!((snapshot.success_rate() - 0.8).abs() < 0.001);
         assert!((snapshot.avg_sync_duration_ms() - 500.0).abs() < 0.001);
         assert!((snapshot.records_per_second() - 200.0).abs() < 0.001);
     }
+
+    #[test]
+    fn test_prometheus_format() {
+        let metrics = Metrics::new();
+        metrics.record_sync(true, 100, 5, 1000);
+
+        let text = metrics.snapshot().to_prometheus();
+        assert!(text.contains("# TYPE supasync_records_synced_total counter\n"));
+        assert!(text.contains("supasync_records_synced_total 100\n"));
+        assert!(text.contains("supasync_sync_duration_ms 1000\n"));
+        assert!(text.contains("# TYPE supasync_success_rate gauge\n"));
+        assert!(text.contains("supasync_success_rate 1\n"));
+    }
+
+    #[test]
+    fn test_per_table_metrics() {
+        let metrics = Metrics::new();
+        metrics.record_table("users", true, 10, 1, 200);
+        metrics.record_table("users", false, 0, 0, 50);
+        metrics.record_table("posts", false, 0, 0, 30);
+
+        let snapshot = metrics.snapshot();
+        let users = &snapshot.per_table["users"];
+        assert_eq!(users.records_synced, 10);
+        assert_eq!(users.records_failed, 1);
+        assert_eq!(users.duration_ms, 250);
+        assert!(users.last_success.is_some());
+        assert!(snapshot.per_table["posts"].last_success.is_none());
+
+        let text = snapshot.to_prometheus();
+        assert!(text.contains("supasync_table_records_synced_total{table=\"users\"} 10\n"));
+        assert!(text.contains("supasync_table_sync_duration_ms{table=\"posts\"} 30\n"));
+        assert!(text.contains("supasync_table_last_success_timestamp_seconds{table=\"users\"}"));
+        assert!(!text.contains("supasync_table_last_success_timestamp_seconds{table=\"posts\"}"));
+        assert_eq!(escape_label("a\"b"), "a\\\"b");
+    }
 }
@@ -1,23 +1,118 @@
 //! MotherDuck client and operations for motherduck-supasync.
 
-use crate::config::{MotherDuckConfig, TableMapping};
+use crate::config::{
+    AnalyticsTablesConfig, MotherDuckConfig, TableMapping, TransactionScope, UpsertStrategy,
+};
 use crate::error::{Error, Result};
-use crate::schema::Table;
-use duckdb::Connection;
+use crate::metrics::Metrics;
+use crate::schema::{
+    Index, IntrospectedColumn, Table, quote_identifier, quote_qualified_identifier,
+};
+use crate::sink::SyncSink;
+use crate::sync::ROW_HASH_COLUMN;
+use crate::throttle::RateLimiter;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use duckdb::types::Value as DuckValue;
+use duckdb::{Connection, appender_params_from_iter, params_from_iter};
 use serde_json::Value as JsonValue;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
 use tracing::{debug, info, instrument, warn};
 
+/// Table recording per-table sync state, including watermarks.
+const SYNC_METADATA_TABLE: &str = "sync_metadata";
+
+/// Temporary table holding live source keys during delete detection.
+const LIVE_KEYS_TABLE: &str = "_supasync_live_keys";
+
+/// Number of keys inserted per statement when loading live keys.
+const LIVE_KEYS_CHUNK_SIZE: usize = 1000;
+
+/// File format for [`MotherDuckClient::export_table`] and [`MotherDuckClient::export_query`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum ExportFormat {
+    /// Apache Parquet (default)
+    #[default]
+    Parquet,
+    /// CSV with a header row
+    Csv,
+}
+
+impl ExportFormat {
+    /// DuckDB `COPY` options for this format.
+    fn copy_options(self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "FORMAT PARQUET",
+            ExportFormat::Csv => "FORMAT CSV, HEADER",
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Parquet => write!(f, "parquet"),
+            ExportFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// A target column whose type no longer matches its source column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Column name as introspected from the source
+    pub column: String,
+    /// DuckDB type of the existing target column
+    pub target_type: String,
+    /// DuckDB type the source column maps to
+    pub source_type: String,
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} in the target but {} in the source",
+            self.column, self.target_type, self.source_type
+        )
+    }
+}
+
+/// Interrupts a MotherDuck connection if it is still busy when the statement
+/// timeout passes. Dropping the watchdog disarms it.
+#[derive(Debug)]
+pub struct StatementWatchdog {
+    _disarm: mpsc::Sender<()>,
+}
+
 /// MotherDuck client wrapper.
 pub struct MotherDuckClient {
     conn: Connection,
     config: MotherDuckConfig,
+    metrics: Arc<Metrics>,
+    limiter: Option<RateLimiter>,
 }
 
 impl MotherDuckClient {
     /// Connect to MotherDuck.
     #[instrument(skip(config), fields(database = %config.database))]
     pub fn connect(config: MotherDuckConfig) -> Result<Self> {
+        if let Some(ref path) = config.local_path {
+            let conn = Connection::open(path)
+                .map_err(|e| Error::motherduck_connection(format!("Failed to open {}", path), e))?;
+            apply_pragmas(&conn, &config.pragmas)?;
+            info!("Opened local DuckDB database: {}", path);
+            return Ok(Self {
+                conn,
+                config,
+                metrics: Arc::new(Metrics::new()),
+                limiter: None,
+            });
+        }
+
         info!("Connecting to MotherDuck...");
 
         // First connect without specifying database to create it if needed
@@ -27,7 +122,10 @@ impl MotherDuckClient {
                 .map_err(|e| Error::motherduck_connection("Failed to connect to MotherDuck", e))?;
 
             // Create database if it doesn't exist
-            let create_db = format!("CREATE DATABASE IF NOT EXISTS {}", config.database);
+            let create_db = format!(
+                "CREATE DATABASE IF NOT EXISTS {}",
+                quote_identifier(&config.database)
+            );
             init_conn
                 .execute(&create_db, [])
                 .map_err(|e| Error::motherduck_query("", "Failed to create database", e))?;
@@ -40,23 +138,96 @@ impl MotherDuckClient {
 
         let conn = Connection::open(&conn_str)
             .map_err(|e| Error::motherduck_connection("Failed to connect to database", e))?;
+        apply_pragmas(&conn, &config.pragmas)?;
 
         info!("Connected to MotherDuck database: {}", config.database);
-        Ok(Self { conn, config })
+        Ok(Self {
+            conn,
+            config,
+            metrics: Arc::new(Metrics::new()),
+            limiter: None,
+        })
+    }
+
+    /// Record queries into a shared metrics collector.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Throttle upserts to `rows_per_second`, or not at all with `None`.
+    pub fn with_rate_limit(mut self, rows_per_second: Option<u32>) -> Self {
+        self.limiter = rows_per_second.map(RateLimiter::new);
+        self
+    }
+
+    /// Interrupt the running statement once `statement_timeout_secs` passes,
+    /// unless the returned watchdog is dropped first.
+    ///
+    /// Returns `None` when no timeout is configured. The interrupted statement
+    /// fails with DuckDB's "Interrupted" error.
+    pub fn statement_watchdog(&self) -> Option<StatementWatchdog> {
+        let secs = self.config.statement_timeout_secs?;
+        let timeout = Duration::from_secs(secs);
+        let handle = self.conn.interrupt_handle();
+        let (disarm, disarmed) = mpsc::channel::<()>();
+
+        std::thread::spawn(move || {
+            if let Err(mpsc::RecvTimeoutError::Timeout) = disarmed.recv_timeout(timeout) {
+                warn!(
+                    "MotherDuck statement exceeded statement_timeout_secs ({}s); interrupting",
+                    secs
+                );
+                handle.interrupt();
+            }
+        });
+
+        Some(StatementWatchdog { _disarm: disarm })
     }
 
     /// Test connectivity.
     pub fn ping(&self) -> Result<()> {
+        self.metrics.record_md_query();
         self.conn
             .execute("SELECT 1", [])
             .map_err(|e| Error::motherduck_query("", "Ping failed", e))?;
         Ok(())
     }
 
+    /// `table` as `schema.table`: in the configured schema unless it names its own.
+    fn qualify(&self, table: &str) -> String {
+        if table.contains('.') {
+            table.to_string()
+        } else {
+            format!("{}.{}", self.config.schema, table)
+        }
+    }
+
+    /// The schema and bare name of `table`, for `information_schema` filters.
+    fn schema_and_name<'a>(&'a self, table: &'a str) -> (&'a str, &'a str) {
+        table
+            .rsplit_once('.')
+            .unwrap_or((self.config.schema.as_str(), table))
+    }
+
+    /// `table_name` filter for catalog queries, comparing case-insensitively
+    /// under `motherduck.case_insensitive_tables`.
+    fn table_name_filter(&self) -> &'static str {
+        if self.config.case_insensitive_tables {
+            "lower(table_name) = lower(?)"
+        } else {
+            "table_name = ?"
+        }
+    }
+
     /// Ensure schema exists.
     pub fn ensure_schema(&self) -> Result<()> {
         if self.config.schema != "main" {
-            let query = format!("CREATE SCHEMA IF NOT EXISTS {}", self.config.schema);
+            self.metrics.record_md_query();
+            let query = format!(
+                "CREATE SCHEMA IF NOT EXISTS {}",
+                quote_identifier(&self.config.schema)
+            );
             self.conn
                 .execute(&query, [])
                 .map_err(|e| Error::motherduck_query("", "Create schema failed", e))?;
@@ -67,126 +238,294 @@ impl MotherDuckClient {
     /// Create table if not exists.
     #[instrument(skip(self), fields(table = %table.name))]
     pub fn create_table(&self, table: &Table) -> Result<()> {
+        let table = Table {
+            name: self.qualify(&table.name),
+            ..table.clone()
+        };
         let ddl = table.to_duckdb_ddl();
         debug!("Creating table with DDL: {}", ddl);
 
+        self.metrics.record_md_query();
         self.conn
             .execute(&ddl, [])
             .map_err(|e| Error::motherduck_query(&table.name, "Create table failed", e))?;
 
-        info!("Created/verified table: {}", table.name);
+        for index in &table.indexes {
+            let ddl = index.to_ddl(&table.name);
+            debug!("Creating index with DDL: {}", ddl);
+
+            self.metrics.record_md_query();
+            self.conn
+                .execute(&ddl, [])
+                .map_err(|e| Error::motherduck_query(&table.name, "Create index failed", e))?;
+        }
+
+        info!(
+            "Created/verified table: {} ({} indexes)",
+            table.name,
+            table.indexes.len()
+        );
         Ok(())
     }
 
-    /// Create default analytics tables.
-    /// DEPRECATED: Use create_table_from_schema instead for dynamic table creation.
-    /// This is kept for backward compatibility with aggregated analytics tables.
-    pub fn create_analytics_tables(&self) -> Result<()> {
-        self.conn
-            .execute_batch(
-                r#"
-            -- Aggregated analytics tables (not synced from PostgreSQL)
-            CREATE TABLE IF NOT EXISTS daily_stats (
-                date DATE PRIMARY KEY,
-                new_users INTEGER,
-                active_users INTEGER,
-                returning_users INTEGER,
-                new_listings INTEGER,
-                completed_shares INTEGER,
-                messages_sent INTEGER,
-                top_categories JSON,
-                computed_at TIMESTAMP
-            );
+    /// Create the configured analytics tables (not synced from PostgreSQL).
+    ///
+    /// Runs each `ddl` statement, then the contents of `sql_file`. Does nothing
+    /// when neither is set.
+    pub fn create_analytics_tables(&self, tables: &AnalyticsTablesConfig) -> Result<()> {
+        if tables.is_empty() {
+            return Ok(());
+        }
 
-            CREATE TABLE IF NOT EXISTS user_activity_summary (
-                user_id VARCHAR PRIMARY KEY,
-                listings_viewed INTEGER,
-                listings_saved INTEGER,
-                messages_initiated INTEGER,
-                shares_completed INTEGER,
-                last_activity_at TIMESTAMP,
-                updated_at TIMESTAMP
-            );
+        for ddl in &tables.ddl {
+            self.metrics.record_md_query();
+            self.conn
+                .execute_batch(ddl)
+                .map_err(|e| Error::motherduck_query("", "Create analytics table failed", e))?;
+        }
 
-            CREATE TABLE IF NOT EXISTS post_activity_daily_stats (
-                id VARCHAR PRIMARY KEY,
-                date DATE,
-                post_type VARCHAR,
-                posts_viewed INTEGER,
-                posts_arranged INTEGER,
-                total_likes INTEGER,
-                updated_at TIMESTAMP
-            );
+        if let Some(ref path) = tables.sql_file {
+            let sql = std::fs::read_to_string(path)
+                .map_err(|e| Error::config_with_source(format!("Failed to read {}", path), e))?;
+            self.metrics.record_md_query();
+            self.conn
+                .execute_batch(&sql)
+                .map_err(|e| Error::motherduck_query("", format!("Running {} failed", path), e))?;
+        }
 
-            -- Events table for tracking
-            CREATE TABLE IF NOT EXISTS events (
-                id VARCHAR PRIMARY KEY,
-                event_name VARCHAR,
-                user_id VARCHAR,
-                properties JSON,
-                timestamp TIMESTAMP
-            );
+        info!("Created/verified analytics tables");
+        Ok(())
+    }
 
-            -- Sync metadata
-            CREATE TABLE IF NOT EXISTS sync_metadata (
+    /// Create the sync metadata table, adding the watermark column to older tables.
+    pub fn ensure_sync_metadata(&self) -> Result<()> {
+        let table = quote_qualified_identifier(&self.qualify(SYNC_METADATA_TABLE));
+        self.metrics.record_md_query();
+        self.conn
+            .execute_batch(&format!(
+                r#"
+            CREATE TABLE IF NOT EXISTS {table} (
                 table_name VARCHAR PRIMARY KEY,
                 last_sync_at TIMESTAMP,
                 records_synced INTEGER,
-                sync_mode VARCHAR
+                sync_mode VARCHAR,
+                watermark VARCHAR
             );
-        "#,
+
+            ALTER TABLE {table} ADD COLUMN IF NOT EXISTS watermark VARCHAR;
+        "#
+            ))
+            .map_err(|e| Error::motherduck_query(SYNC_METADATA_TABLE, "Create failed", e))
+    }
+
+    /// Get the last stored watermark for a target table.
+    ///
+    /// Returns `None` if no watermark has been recorded yet.
+    pub fn get_watermark(&self, table: &str) -> Result<Option<JsonValue>> {
+        // Reading must not create anything, so tolerate a missing table or column
+        if !self
+            .table_columns(SYNC_METADATA_TABLE)?
+            .iter()
+            .any(|c| c == "watermark")
+        {
+            return Ok(None);
+        }
+
+        self.metrics.record_md_query();
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT watermark FROM {} WHERE table_name = ?",
+                quote_qualified_identifier(&self.qualify(SYNC_METADATA_TABLE))
+            ))
+            .map_err(|e| Error::motherduck_query(SYNC_METADATA_TABLE, "Prepare failed", e))?;
+
+        let stored: Vec<Option<String>> = stmt
+            .query_map([table], |row| row.get(0))
+            .map_err(|e| Error::motherduck_query(SYNC_METADATA_TABLE, "Read watermark failed", e))?
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|e| {
+                Error::motherduck_query(SYNC_METADATA_TABLE, "Read watermark failed", e)
+            })?;
+
+        // Watermarks are stored as JSON text so numbers and strings round-trip
+        match stored.into_iter().flatten().next() {
+            Some(text) => serde_json::from_str(&text)
+                .map(Some)
+                .map_err(|e| Error::Serialization {
+                    message: format!("Invalid stored watermark for {}: {}", table, text),
+                    source: Some(Box::new(e)),
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Record a sync's watermark for a target table.
+    pub fn set_watermark(
+        &self,
+        table: &str,
+        watermark: &JsonValue,
+        records_synced: usize,
+        sync_mode: &str,
+    ) -> Result<()> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT OR REPLACE INTO {} \
+                     (table_name, last_sync_at, records_synced, sync_mode, watermark) \
+                     VALUES (?, now(), ?, ?, ?)",
+                    quote_qualified_identifier(&self.qualify(SYNC_METADATA_TABLE))
+                ),
+                params_from_iter([
+                    DuckValue::Text(table.to_string()),
+                    DuckValue::BigInt(records_synced as i64),
+                    DuckValue::Text(sync_mode.to_string()),
+                    DuckValue::Text(watermark.to_string()),
+                ]),
+            )
+            .map_err(|e| {
+                Error::motherduck_query(SYNC_METADATA_TABLE, "Write watermark failed", e)
+            })?;
+
+        debug!("Stored watermark {} for {}", watermark, table);
+        Ok(())
+    }
+
+    /// Record when a target table last synced, how many rows it got and in which mode.
+    ///
+    /// A stored watermark is left as it is.
+    pub fn record_sync(&self, table: &str, records_synced: usize, sync_mode: &str) -> Result<()> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(
+                &format!(
+                    "INSERT INTO {} (table_name, last_sync_at, records_synced, sync_mode) \
+                     VALUES (?, now(), ?, ?) \
+                     ON CONFLICT (table_name) DO UPDATE SET \
+                     last_sync_at = excluded.last_sync_at, \
+                     records_synced = excluded.records_synced, \
+                     sync_mode = excluded.sync_mode",
+                    quote_qualified_identifier(&self.qualify(SYNC_METADATA_TABLE))
+                ),
+                params_from_iter([
+                    DuckValue::Text(table.to_string()),
+                    DuckValue::BigInt(records_synced as i64),
+                    DuckValue::Text(sync_mode.to_string()),
+                ]),
             )
-            .map_err(|e| Error::motherduck_query("", "Create analytics tables failed", e))?;
+            .map_err(|e| {
+                Error::motherduck_query(SYNC_METADATA_TABLE, "Write sync metadata failed", e)
+            })?;
 
-        info!("Created/verified aggregated analytics tables");
         Ok(())
     }
 
     /// Create a target table dynamically based on introspected schema.
     /// This ensures the MotherDuck table matches the PostgreSQL source schema.
-    #[instrument(skip(self, columns), fields(table = %target_table))]
+    /// Indexes touching the sync flag column are skipped along with the column.
+    /// Columns listed in `type_overrides` get that DuckDB type instead of the
+    /// inferred one.
+    #[instrument(skip(self, columns, indexes, type_overrides), fields(table = %target_table))]
     pub fn create_table_from_schema(
         &self,
         target_table: &str,
         columns: &[crate::schema::IntrospectedColumn],
         primary_key: &[String],
+        indexes: &[crate::schema::Index],
+        type_overrides: &HashMap<String, String>,
     ) -> Result<()> {
-        use crate::schema::{Column, ColumnType, Table};
+        let table = table_from_schema(target_table, columns, primary_key, indexes, type_overrides);
+        self.create_table(&table)?;
+
+        info!("Created/verified table from schema: {}", target_table);
+        Ok(())
+    }
+
+    /// Add columns present in the source schema but missing from the target table.
+    ///
+    /// New columns are always nullable since existing rows have no value for them.
+    /// Column names are compared case-insensitively, as DuckDB does. Returns the
+    /// names of the columns that were added.
+    #[instrument(skip(self, columns, type_overrides), fields(table = %target_table))]
+    pub fn reconcile_schema(
+        &self,
+        target_table: &str,
+        columns: &[crate::schema::IntrospectedColumn],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<String>> {
+        let existing: Vec<String> = self
+            .table_columns(target_table)?
+            .iter()
+            .map(|c| c.to_lowercase())
+            .collect();
 
-        let mut table = Table::new(target_table);
+        if existing.is_empty() {
+            return Err(Error::schema(format!(
+                "Target table {} has no columns or doesn't exist",
+                target_table
+            )));
+        }
 
+        let mut added = Vec::new();
         for col in columns {
-            // Skip the sync flag column - it's internal to PostgreSQL
-            if col.name == "synced_to_motherduck" {
+            if existing.contains(&col.name.to_lowercase()) {
                 continue;
             }
 
-            let column = Column::new(col.name.clone(), ColumnType::from_postgres(&col.pg_type))
-                .nullable(col.nullable);
+            let ddl = format!(
+                "ALTER TABLE {} ADD COLUMN IF NOT EXISTS {} {}",
+                quote_qualified_identifier(&self.qualify(target_table)),
+                quote_identifier(&col.name),
+                col.column_type(type_overrides).to_duckdb_ddl_fragment()
+            );
+            info!("Migrating schema: {}", ddl);
 
-            table.add_column(column);
-        }
+            self.metrics.record_md_query();
+            self.conn
+                .execute(&ddl, [])
+                .map_err(|e| Error::motherduck_query(target_table, "Add column failed", e))?;
 
-        if !primary_key.is_empty() {
-            table.set_primary_key(primary_key.to_vec());
+            added.push(col.name.clone());
         }
 
-        let ddl = table.to_duckdb_ddl();
-        debug!("Creating table with DDL: {}", ddl);
-
-        self.conn.execute(&ddl, []).map_err(|e| {
-            Error::motherduck_query(target_table, "Create table from schema failed", e)
-        })?;
+        Ok(added)
+    }
 
-        info!("Created/verified table from schema: {}", target_table);
-        Ok(())
+    /// Compare the types of existing target columns against the mapped source types.
+    ///
+    /// Columns missing from the target are not mismatches (see
+    /// [`reconcile_schema`](Self::reconcile_schema)). Type parameters such as
+    /// `DECIMAL(10, 2)` or `VARCHAR(255)` are ignored; only the base type counts.
+    /// A column in `type_overrides` is compared against its override.
+    pub fn check_schema_compatibility(
+        &self,
+        target_table: &str,
+        columns: &[crate::schema::IntrospectedColumn],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<SchemaMismatch>> {
+        let existing = self.column_types(target_table)?;
+        Ok(schema_mismatches(&existing, columns, type_overrides))
     }
 
     /// Check if a table exists and has the expected columns.
     pub fn table_has_columns(&self, table: &str, expected_columns: &[&str]) -> Result<bool> {
+        let existing_columns = self.table_columns(table)?;
+
+        // Check if all expected columns exist
+        let all_exist = expected_columns
+            .iter()
+            .all(|col| existing_columns.iter().any(|c| c == *col));
+
+        Ok(all_exist)
+    }
+
+    /// Get column names for a table in ordinal order.
+    pub fn table_columns(&self, table: &str) -> Result<Vec<String>> {
+        self.metrics.record_md_query();
         let query = format!(
-            "SELECT column_name FROM information_schema.columns WHERE table_name = '{}'",
-            table
+            "SELECT column_name FROM information_schema.columns WHERE table_catalog = current_database() AND table_schema = ? AND {} ORDER BY ordinal_position",
+            self.table_name_filter()
         );
 
         let mut stmt = self
@@ -194,21 +533,71 @@ impl MotherDuckClient {
             .prepare(&query)
             .map_err(|e| Error::motherduck_query(table, "Check columns failed", e))?;
 
-        let existing_columns: Vec<String> = stmt
-            .query_map([], |row| row.get(0))
+        let (schema, name) = self.schema_and_name(table);
+        let columns: Vec<String> = stmt
+            .query_map([schema, name], |row| row.get(0))
             .map_err(|e| Error::motherduck_query(table, "Query columns failed", e))?
             .filter_map(|r| r.ok())
             .collect();
 
-        // Check if all expected columns exist
-        let all_exist = expected_columns
-            .iter()
-            .all(|col| existing_columns.iter().any(|c| c == *col));
+        Ok(columns)
+    }
 
-        Ok(all_exist)
+    /// Get a table's column types (DuckDB `data_type` names), keyed by column name.
+    pub fn column_types(&self, table: &str) -> Result<HashMap<String, String>> {
+        Ok(self.column_definitions(table)?.into_iter().collect())
+    }
+
+    /// Get a table's column names and DuckDB `data_type` names in ordinal order.
+    pub fn column_definitions(&self, table: &str) -> Result<Vec<(String, String)>> {
+        self.metrics.record_md_query();
+        let query = format!(
+            "SELECT column_name, data_type FROM information_schema.columns WHERE table_catalog = current_database() AND table_schema = ? AND {} ORDER BY ordinal_position",
+            self.table_name_filter()
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .map_err(|e| Error::motherduck_query(table, "Check column types failed", e))?;
+
+        let (schema, name) = self.schema_and_name(table);
+        let columns: Vec<(String, String)> = stmt
+            .query_map([schema, name], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| Error::motherduck_query(table, "Query column types failed", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(columns)
+    }
+
+    /// Check if a table has a primary key or unique constraint that inserts can conflict on.
+    pub fn has_conflict_target(&self, table: &str) -> Result<bool> {
+        self.metrics.record_md_query();
+        let query = format!(
+            "SELECT COUNT(*) FROM duckdb_constraints() WHERE database_name = current_database() AND schema_name = ? AND {} AND constraint_type IN ('PRIMARY KEY', 'UNIQUE')",
+            self.table_name_filter()
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .map_err(|e| Error::motherduck_query(table, "Check constraints failed", e))?;
+
+        let (schema, name) = self.schema_and_name(table);
+        let count: i64 = stmt
+            .query_row([schema, name], |row| row.get(0))
+            .map_err(|e| Error::motherduck_query(table, "Check constraints failed", e))?;
+
+        Ok(count > 0)
     }
 
-    /// Insert or replace rows using bulk VALUES syntax for better performance.
+    /// Insert or replace rows.
+    ///
+    /// Tables without a primary key or unique constraint are loaded through the
+    /// Appender API. Tables that need conflict resolution fall back to a prepared
+    /// statement with bound parameters, built by [`upsert_statement`] from the
+    /// mapping's [`UpsertStrategy`].
     #[instrument(skip(self, rows), fields(table = %mapping.target_table, count = rows.len()))]
     pub fn upsert_rows(
         &self,
@@ -219,129 +608,711 @@ impl MotherDuckClient {
             return Ok(0);
         }
 
-        // Get column names from first row (sorted for consistency)
-        let mut columns: Vec<&String> = rows[0].keys().collect();
-        columns.sort();
-        let col_names = columns
-            .iter()
-            .map(|c| c.as_str())
-            .collect::<Vec<_>>()
-            .join(", ");
+        if !self.has_conflict_target(&mapping.target_table)? {
+            return self.append_rows(mapping, rows);
+        }
 
-        // Build bulk VALUES clause for all rows
-        let mut values_parts: Vec<String> = Vec::with_capacity(rows.len());
+        if let Some(ref limiter) = self.limiter {
+            limiter.throttle(rows.len());
+        }
+
+        // Write the first row's columns in the target table's column order
+        let written = written_columns(mapping, &rows[0]);
+        self.check_unwritten_columns(mapping, &written)?;
+        let definitions = self.column_definitions(&mapping.target_table)?;
+        let order: Vec<&str> = definitions.iter().map(|(name, _)| name.as_str()).collect();
+        let columns = insert_columns(written.into_iter(), &order);
+        let types: HashMap<String, String> = definitions.into_iter().collect();
+        let target = self.qualify(&mapping.target_table);
+        let query = upsert_statement(&target, mapping, &columns, &types);
+
+        self.metrics.record_md_query();
+        let mut stmt = self.conn.prepare(&query).map_err(|e| {
+            Error::motherduck_query(&mapping.target_table, "Prepare insert failed", e)
+        })?;
+
+        // Rows a conflict leaves alone, such as unchanged row hashes, count 0
+        let mut written = 0;
         for row in rows {
-            let row_values: Vec<String> = columns
+            let values = columns
                 .iter()
-                .map(|col| {
-                    let value = row.get(*col).unwrap_or(&JsonValue::Null);
-                    json_to_sql_literal(value)
-                })
-                .collect();
-            values_parts.push(format!("({})", row_values.join(", ")));
+                .map(|col| column_value(mapping, row, col, &types));
+
+            written += stmt.execute(params_from_iter(values)).map_err(|e| {
+                Error::motherduck_query(&mapping.target_table, "Bulk insert failed", e)
+            })?;
         }
 
-        let query = format!(
-            "INSERT OR REPLACE INTO {} ({}) VALUES {}",
-            mapping.target_table,
-            col_names,
-            values_parts.join(", ")
+        debug!(
+            "Bulk upserted {} of {} rows to {}",
+            written,
+            rows.len(),
+            mapping.target_table
         );
+        Ok(written)
+    }
 
-        self.conn
+    /// The statements a row sync of `mapping` would run against MotherDuck,
+    /// without running them.
+    ///
+    /// `columns` are the source columns being synced. With `create`, a target that
+    /// doesn't exist yet gets its `CREATE TABLE` and index DDL first, and the
+    /// upsert assumes the primary key it would be created with. Targets without a
+    /// conflict target show the plain `INSERT` the Appender amounts to. Soft-delete
+    /// tables end with the `DELETE` for their deleted rows.
+    #[instrument(skip(self, columns, indexes), fields(table = %mapping.target_table))]
+    pub fn preview_writes(
+        &self,
+        mapping: &TableMapping,
+        columns: &[crate::schema::IntrospectedColumn],
+        indexes: &[Index],
+        create: bool,
+    ) -> Result<Vec<String>> {
+        let target = self.qualify(&mapping.target_table);
+        let mut statements = Vec::new();
+        let (definitions, conflict) = if self.table_exists(&mapping.target_table)? {
+            (
+                self.column_definitions(&mapping.target_table)?,
+                self.has_conflict_target(&mapping.target_table)?,
+            )
+        } else {
+            let table = table_from_schema(
+                &target,
+                columns,
+                &mapping.primary_key,
+                indexes,
+                &mapping.type_overrides,
+            );
+            if create {
+                statements.push(table.to_duckdb_ddl());
+                statements.extend(table.indexes.iter().map(|index| index.to_ddl(&table.name)));
+            }
+            let definitions = table
+                .columns
+                .iter()
+                .map(|c| (c.name.clone(), c.column_type.to_duckdb_ddl_fragment()))
+                .collect();
+            (definitions, !mapping.primary_key.is_empty())
+        };
+
+        // Rows never carry the sync flag column
+        let mut names: Vec<String> = columns
+            .iter()
+            .filter(|c| mapping.sync_flag_column.as_ref() != Some(&c.name))
+            .map(|c| c.name.clone())
+            .collect();
+        let defaults: Vec<String> = mapping
+            .column_defaults
+            .keys()
+            .filter(|c| !names.contains(c))
+            .cloned()
+            .collect();
+        names.extend(defaults);
+        let order: Vec<&str> = definitions.iter().map(|(name, _)| name.as_str()).collect();
+        let insert = insert_columns(names.iter(), &order);
+        let types: HashMap<String, String> = definitions.into_iter().collect();
+        statements.push(if conflict {
+            upsert_statement(&target, mapping, &insert, &types)
+        } else {
+            insert_statement(&target, &insert, &types, ("INSERT INTO", String::new()))
+        });
+
+        if mapping.soft_delete_column.is_some() {
+            statements.push(delete_statement(&target, mapping));
+        }
+
+        Ok(statements)
+    }
+
+    /// Upsert every row of a CSV file with a header row in one statement.
+    ///
+    /// Fields are read as text and cast to the target columns' types. Unquoted
+    /// empty fields are NULL and quoted ones empty strings, as in PostgreSQL's
+    /// CSV output. Returns the number of rows written.
+    #[instrument(skip(self, mapping, columns), fields(table = %mapping.target_table))]
+    pub fn load_csv(
+        &self,
+        mapping: &TableMapping,
+        columns: &[String],
+        path: &str,
+    ) -> Result<usize> {
+        let upsert = self.has_conflict_target(&mapping.target_table)?;
+        let target = self.qualify(&mapping.target_table);
+        let query = load_csv_statement(&target, mapping, columns, path, upsert);
+
+        self.metrics.record_md_query();
+        let rows = self
+            .conn
             .execute(&query, [])
-            .map_err(|e| Error::motherduck_query(&mapping.target_table, "Bulk insert failed", e))?;
+            .map_err(|e| Error::motherduck_query(&mapping.target_table, "Load CSV failed", e))?;
 
         debug!(
-            "Bulk upserted {} rows to {}",
-            rows.len(),
-            mapping.target_table
+            "Loaded {} rows from {} to {}",
+            rows, path, mapping.target_table
         );
-        Ok(rows.len())
+        Ok(rows)
     }
 
-    /// Batch upsert with transaction.
+    /// Append rows using DuckDB's Appender API.
+    ///
+    /// Values are bound per column with native types instead of being rendered into
+    /// SQL text; the Appender casts text such as ISO 8601 timestamps to the column's
+    /// type. The Appender only performs plain inserts, so rows that collide with
+    /// a primary key will fail rather than replace the existing row.
     #[instrument(skip(self, rows), fields(table = %mapping.target_table, count = rows.len()))]
-    pub fn batch_upsert(
+    pub fn append_rows(
         &self,
         mapping: &TableMapping,
         rows: &[HashMap<String, JsonValue>],
-        batch_size: usize,
     ) -> Result<usize> {
         if rows.is_empty() {
             return Ok(0);
         }
 
-        let mut total = 0;
+        if let Some(ref limiter) = self.limiter {
+            limiter.throttle(rows.len());
+        }
 
-        // Process in batches
-        for chunk in rows.chunks(batch_size) {
-            // Start transaction
-            self.conn.execute("BEGIN TRANSACTION", []).map_err(|e| {
-                Error::motherduck_query(&mapping.target_table, "Begin transaction failed", e)
-            })?;
+        // The Appender expects a value for every column in table order
+        let definitions = self.column_definitions(&mapping.target_table)?;
+        let columns: Vec<&String> = definitions.iter().map(|(name, _)| name).collect();
+        let types: HashMap<String, String> = definitions.iter().cloned().collect();
+        if columns.is_empty() {
+            return Err(Error::schema(format!(
+                "Target table {} has no columns or doesn't exist",
+                mapping.target_table
+            )));
+        }
 
-            match self.upsert_rows(mapping, chunk) {
-                Ok(count) => {
-                    self.conn.execute("COMMIT", []).map_err(|e| {
-                        Error::motherduck_query(&mapping.target_table, "Commit failed", e)
-                    })?;
-                    total += count;
-                }
-                Err(e) => {
-                    let _ = self.conn.execute("ROLLBACK", []);
-                    return Err(e);
-                }
-            }
+        // The Appender writes NULL for columns it isn't given, never their DEFAULT
+        let written = written_columns(mapping, &rows[0]);
+        if self.check_unwritten_columns(mapping, &written)? {
+            return self.insert_rows(mapping, rows, &written);
         }
 
-        info!("Batch upserted {} rows to {}", total, mapping.target_table);
-        Ok(total)
+        let (schema, name) = self.schema_and_name(&mapping.target_table);
+        self.metrics.record_md_query();
+        let mut appender = self.conn.appender_to_db(name, schema).map_err(|e| {
+            Error::motherduck_query(&mapping.target_table, "Create appender failed", e)
+        })?;
+
+        for row in rows {
+            let values = columns
+                .iter()
+                .map(|col| column_value(mapping, row, col, &types));
+
+            appender
+                .append_row(appender_params_from_iter(values))
+                .map_err(|e| {
+                    Error::motherduck_query(&mapping.target_table, "Append row failed", e)
+                })?;
+        }
+
+        appender.flush().map_err(|e| {
+            Error::motherduck_query(&mapping.target_table, "Appender flush failed", e)
+        })?;
+
+        debug!("Appended {} rows to {}", rows.len(), mapping.target_table);
+        Ok(rows.len())
     }
 
-    /// Get row count for a table.
-    pub fn count_rows(&self, table: &str) -> Result<i64> {
-        let query = format!("SELECT COUNT(*) FROM {}", table);
-        let mut stmt = self
-            .conn
-            .prepare(&query)
-            .map_err(|e| Error::motherduck_query(table, "Prepare count failed", e))?;
+    /// Insert rows with a plain `INSERT` of `written`, so DuckDB fills every
+    /// other column from its DEFAULT.
+    fn insert_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+        written: &[&String],
+    ) -> Result<usize> {
+        let definitions = self.column_definitions(&mapping.target_table)?;
+        let order: Vec<&str> = definitions.iter().map(|(name, _)| name.as_str()).collect();
+        let columns = insert_columns(written.iter().copied(), &order);
+        let types: HashMap<String, String> = definitions.into_iter().collect();
+        let target = self.qualify(&mapping.target_table);
+        let query = insert_statement(&target, &columns, &types, ("INSERT INTO", String::new()));
 
-        let count: i64 = stmt
-            .query_row([], |row| row.get(0))
-            .map_err(|e| Error::motherduck_query(table, "Count failed", e))?;
+        self.metrics.record_md_query();
+        let mut stmt = self.conn.prepare(&query).map_err(|e| {
+            Error::motherduck_query(&mapping.target_table, "Prepare insert failed", e)
+        })?;
 
-        Ok(count)
+        for row in rows {
+            let values = columns
+                .iter()
+                .map(|col| column_value(mapping, row, col, &types));
+
+            stmt.execute(params_from_iter(values)).map_err(|e| {
+                Error::motherduck_query(&mapping.target_table, "Insert row failed", e)
+            })?;
+        }
+
+        debug!("Inserted {} rows to {}", rows.len(), mapping.target_table);
+        Ok(rows.len())
     }
 
-    /// Check if table exists.
-    pub fn table_exists(&self, table: &str) -> Result<bool> {
+    /// Check the target columns a write of `written` leaves out.
+    ///
+    /// A NOT NULL column without a DEFAULT fails here, naming the column, rather
+    /// than mid-batch. Returns whether any column left out has a DEFAULT.
+    fn check_unwritten_columns(&self, mapping: &TableMapping, written: &[&String]) -> Result<bool> {
+        let table = &mapping.target_table;
+        self.metrics.record_md_query();
         let query = format!(
-            "SELECT COUNT(*) FROM information_schema.tables WHERE table_name = '{}'",
-            table
+            "SELECT column_name, is_nullable = 'YES', column_default IS NOT NULL FROM information_schema.columns WHERE table_catalog = current_database() AND table_schema = ? AND {} ORDER BY ordinal_position",
+            self.table_name_filter()
         );
 
         let mut stmt = self
             .conn
             .prepare(&query)
-            .map_err(|e| Error::motherduck_query(table, "Check table exists failed", e))?;
+            .map_err(|e| Error::motherduck_query(table, "Check columns failed", e))?;
 
-        let count: i64 = stmt
-            .query_row([], |row| row.get(0))
-            .map_err(|e| Error::motherduck_query(table, "Check table exists failed", e))?;
+        let (schema, name) = self.schema_and_name(table);
+        let columns: Vec<(String, bool, bool)> = stmt
+            .query_map([schema, name], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .map_err(|e| Error::motherduck_query(table, "Query columns failed", e))?
+            .filter_map(|r| r.ok())
+            .collect();
 
-        Ok(count > 0)
+        let mut has_default = false;
+        for (name, nullable, default) in columns {
+            if written.iter().any(|c| c.eq_ignore_ascii_case(&name)) {
+                continue;
+            }
+            if !nullable && !default {
+                return Err(Error::schema(format!(
+                    "Column {} of {} is NOT NULL without a default and not synced; \
+                     set a value for it in column_defaults",
+                    name, table
+                )));
+            }
+            has_default |= default;
+        }
+        Ok(has_default)
     }
 
-    /// Execute raw SQL.
-    pub fn execute(&self, sql: &str) -> Result<usize> {
-        self.conn
-            .execute(sql, [])
-            .map_err(|e| Error::motherduck_query("", "Execute failed", e))
+    /// Batch upsert with transaction.
+    ///
+    /// With [`TransactionScope::PerBatch`] each chunk of `batch_size` rows is
+    /// committed on its own; with [`TransactionScope::PerTable`] all chunks share
+    /// one transaction, which also runs the mapping's `post_sql`, and a failure
+    /// rolls back every chunk. `progress` is called after each chunk with the
+    /// number of rows written so far.
+    #[instrument(skip(self, mapping, rows, progress), fields(table = %mapping.target_table, count = rows.len()))]
+    pub fn batch_upsert(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+        batch_size: usize,
+        scope: TransactionScope,
+        mut progress: impl FnMut(usize),
+    ) -> Result<usize> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+
+        let total = match scope {
+            TransactionScope::PerBatch => {
+                let mut total = 0;
+                for chunk in rows.chunks(batch_size) {
+                    total += self.in_transaction(&mapping.target_table, || {
+                        self.upsert_rows(mapping, chunk)
+                    })?;
+                    progress(total);
+                }
+                total
+            }
+            TransactionScope::PerTable => self.in_transaction(&mapping.target_table, || {
+                let mut total = 0;
+                for chunk in rows.chunks(batch_size) {
+                    total += self.upsert_rows(mapping, chunk)?;
+                    progress(total);
+                }
+                self.run_post_sql(mapping)?;
+                Ok(total)
+            })?,
+        };
+
+        info!("Batch upserted {} rows to {}", total, mapping.target_table);
+        Ok(total)
+    }
+
+    /// Run `f` inside a transaction, committing on success and rolling back on error.
+    fn in_transaction<T>(&self, table: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        self.begin(table)?;
+        match f() {
+            Ok(value) => {
+                self.commit(table)?;
+                Ok(value)
+            }
+            Err(e) => {
+                self.rollback();
+                Err(e)
+            }
+        }
+    }
+
+    /// Start a transaction for writes to `table`.
+    pub fn begin(&self, table: &str) -> Result<()> {
+        self.conn
+            .execute("BEGIN TRANSACTION", [])
+            .map(|_| ())
+            .map_err(|e| Error::motherduck_query(table, "Begin transaction failed", e))
+    }
+
+    /// Commit the open transaction.
+    pub fn commit(&self, table: &str) -> Result<()> {
+        self.conn
+            .execute("COMMIT", [])
+            .map(|_| ())
+            .map_err(|e| Error::motherduck_query(table, "Commit failed", e))
+    }
+
+    /// Roll back the open transaction, ignoring errors (e.g. none was open).
+    pub fn rollback(&self) {
+        let _ = self.conn.execute("ROLLBACK", []);
+    }
+
+    /// Delete target rows whose primary key is not in `live_keys`.
+    ///
+    /// Each key holds one value per primary key column, in `mapping.primary_key`
+    /// order. Keys are loaded into a temporary table in chunks and the delete runs
+    /// as a single anti-join, so a chunk never deletes rows that a later chunk
+    /// would have kept. An empty key set deletes nothing, to avoid wiping the
+    /// target when the source returns no rows.
+    #[instrument(skip(self, live_keys), fields(table = %mapping.target_table, keys = live_keys.len()))]
+    pub fn delete_missing(
+        &self,
+        mapping: &TableMapping,
+        live_keys: &[Vec<JsonValue>],
+    ) -> Result<usize> {
+        if live_keys.is_empty() {
+            warn!(
+                "No live keys for {}, skipping delete detection",
+                mapping.target_table
+            );
+            return Ok(0);
+        }
+
+        let target = quote_qualified_identifier(&self.qualify(&mapping.target_table));
+        let key_cols: Vec<String> = mapping
+            .primary_key
+            .iter()
+            .map(|c| quote_identifier(c))
+            .collect();
+        let live = quote_identifier(LIVE_KEYS_TABLE);
+
+        // Copy the key column types from the target so comparisons don't need casts
+        self.execute_batch(&format!(
+            "CREATE OR REPLACE TEMP TABLE {} AS SELECT {} FROM {} LIMIT 0",
+            live,
+            key_cols.join(", "),
+            target
+        ))?;
+
+        let result = self.load_live_keys(mapping, &live, key_cols.len(), live_keys);
+        let result = result.and_then(|()| {
+            let matches = key_cols
+                .iter()
+                .map(|c| format!("k.{c} = {target}.{c}"))
+                .collect::<Vec<_>>()
+                .join(" AND ");
+            let query = format!(
+                "DELETE FROM {} WHERE NOT EXISTS (SELECT 1 FROM {} k WHERE {})",
+                target, live, matches
+            );
+
+            self.metrics.record_md_query();
+            self.conn.execute(&query, []).map_err(|e| {
+                Error::motherduck_query(&mapping.target_table, "Delete missing rows failed", e)
+            })
+        });
+
+        let _ = self
+            .conn
+            .execute(&format!("DROP TABLE IF EXISTS {}", live), []);
+
+        let deleted = result?;
+        debug!(
+            "Deleted {} rows missing from source in {}",
+            deleted, mapping.target_table
+        );
+        Ok(deleted)
+    }
+
+    /// Delete the target rows with the given primary keys.
+    ///
+    /// Each key holds one value per primary key column, in `mapping.primary_key`
+    /// order. Keys without a target row are ignored. Returns the number of rows
+    /// deleted.
+    #[instrument(skip(self, keys), fields(table = %mapping.target_table, keys = keys.len()))]
+    pub fn delete_rows(&self, mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<usize> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+
+        self.metrics.record_md_query();
+        let query = delete_statement(&self.qualify(&mapping.target_table), mapping);
+        let mut stmt = self.conn.prepare(&query).map_err(|e| {
+            Error::motherduck_query(&mapping.target_table, "Prepare delete failed", e)
+        })?;
+
+        let mut deleted = 0;
+        for key in keys {
+            deleted += stmt
+                .execute(params_from_iter(key.iter().map(json_to_duckdb_value)))
+                .map_err(|e| Error::motherduck_query(&mapping.target_table, "Delete failed", e))?;
+        }
+
+        debug!("Deleted {} rows from {}", deleted, mapping.target_table);
+        Ok(deleted)
+    }
+
+    /// Insert live keys into the temporary key table in chunks.
+    fn load_live_keys(
+        &self,
+        mapping: &TableMapping,
+        live: &str,
+        width: usize,
+        live_keys: &[Vec<JsonValue>],
+    ) -> Result<()> {
+        let row_placeholder = format!("({})", vec!["?"; width].join(", "));
+
+        for chunk in live_keys.chunks(LIVE_KEYS_CHUNK_SIZE) {
+            let query = format!(
+                "INSERT INTO {} VALUES {}",
+                live,
+                vec![row_placeholder.as_str(); chunk.len()].join(", ")
+            );
+            let values = chunk.iter().flatten().map(json_to_duckdb_value);
+
+            self.metrics.record_md_query();
+            self.conn
+                .execute(&query, params_from_iter(values))
+                .map_err(|e| {
+                    Error::motherduck_query(&mapping.target_table, "Load live keys failed", e)
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Get row count for a table.
+    pub fn count_rows(&self, table: &str) -> Result<i64> {
+        self.metrics.record_md_query();
+        let query = format!(
+            "SELECT COUNT(*) FROM {}",
+            quote_qualified_identifier(&self.qualify(table))
+        );
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .map_err(|e| Error::motherduck_query(table, "Prepare count failed", e))?;
+
+        let count: i64 = stmt
+            .query_row([], |row| row.get(0))
+            .map_err(|e| Error::motherduck_query(table, "Count failed", e))?;
+
+        Ok(count)
+    }
+
+    /// Drop a table if it exists.
+    pub fn drop_table(&self, table: &str) -> Result<()> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(
+                &format!(
+                    "DROP TABLE IF EXISTS {}",
+                    quote_qualified_identifier(&self.qualify(table))
+                ),
+                [],
+            )
+            .map(|_| ())
+            .map_err(|e| Error::motherduck_query(table, "Drop table failed", e))
+    }
+
+    /// Delete every row of a table, keeping its structure.
+    ///
+    /// Returns the number of rows deleted.
+    pub fn truncate_table(&self, table: &str) -> Result<usize> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(
+                &format!(
+                    "DELETE FROM {}",
+                    quote_qualified_identifier(&self.qualify(table))
+                ),
+                [],
+            )
+            .map_err(|e| Error::motherduck_query(table, "Truncate table failed", e))
+    }
+
+    /// Refresh a table's query planner statistics with `ANALYZE`.
+    pub fn analyze_table(&self, table: &str) -> Result<()> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(
+                &format!(
+                    "ANALYZE {}",
+                    quote_qualified_identifier(&self.qualify(table))
+                ),
+                [],
+            )
+            .map(|_| ())
+            .map_err(|e| Error::motherduck_query(table, "Analyze failed", e))
+    }
+
+    /// Run the mapping's `post_sql`, if any, with `{{target}}` replaced by the
+    /// qualified target table.
+    pub fn run_post_sql(&self, mapping: &TableMapping) -> Result<()> {
+        let Some(ref sql) = mapping.post_sql else {
+            return Ok(());
+        };
+        let target = quote_qualified_identifier(&self.qualify(&mapping.target_table));
+        let sql = sql.replace("{{target}}", &target);
+        debug!("Running post_sql for {}: {}", mapping.target_table, sql);
+        self.metrics.record_md_query();
+        self.conn
+            .execute_batch(&sql)
+            .map_err(|e| Error::motherduck_query(&mapping.target_table, "post_sql failed", e))
+    }
+
+    /// Write the database's write-ahead log into its storage with `CHECKPOINT`.
+    pub fn checkpoint(&self) -> Result<()> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute("CHECKPOINT", [])
+            .map(|_| ())
+            .map_err(|e| Error::motherduck_query("", "Checkpoint failed", e))
+    }
+
+    /// Replace `target` with the fully loaded `staging` table in one transaction.
+    ///
+    /// DuckDB can't rename a table that has indexes, so the staging table is
+    /// loaded without any and `indexes` are created on the swapped-in table
+    /// before the commit. Readers see either the old table or the new one.
+    #[instrument(skip(self, indexes), fields(table = %target))]
+    pub fn swap_table(&self, staging: &str, target: &str, indexes: &[Index]) -> Result<()> {
+        // RENAME TO takes a bare name; the table stays in its schema
+        let (_, name) = self.schema_and_name(target);
+        let qualified = self.qualify(target);
+        let mut statements = vec![
+            format!(
+                "DROP TABLE IF EXISTS {}",
+                quote_qualified_identifier(&qualified)
+            ),
+            format!(
+                "ALTER TABLE {} RENAME TO {}",
+                quote_qualified_identifier(&self.qualify(staging)),
+                quote_identifier(name)
+            ),
+        ];
+        statements.extend(indexes.iter().map(|index| index.to_ddl(&qualified)));
+
+        self.begin(target)?;
+        for statement in &statements {
+            debug!("Swapping table: {}", statement);
+            self.metrics.record_md_query();
+            if let Err(e) = self.conn.execute(statement, []) {
+                self.rollback();
+                return Err(Error::motherduck_query(target, "Swap table failed", e));
+            }
+        }
+        self.commit(target)?;
+
+        info!("Swapped {} into {}", staging, target);
+        Ok(())
+    }
+
+    /// Check if table exists.
+    pub fn table_exists(&self, table: &str) -> Result<bool> {
+        self.metrics.record_md_query();
+        let query = format!(
+            "SELECT COUNT(*) FROM information_schema.tables WHERE table_catalog = current_database() AND table_schema = ? AND {}",
+            self.table_name_filter()
+        );
+
+        let mut stmt = self
+            .conn
+            .prepare(&query)
+            .map_err(|e| Error::motherduck_query(table, "Check table exists failed", e))?;
+
+        let (schema, name) = self.schema_and_name(table);
+        let count: i64 = stmt
+            .query_row([schema, name], |row| row.get(0))
+            .map_err(|e| Error::motherduck_query(table, "Check table exists failed", e))?;
+
+        Ok(count > 0)
+    }
+
+    /// List the base tables in the configured schema, sorted by name.
+    pub fn list_tables(&self) -> Result<Vec<String>> {
+        self.metrics.record_md_query();
+        let query = "SELECT table_name FROM information_schema.tables WHERE table_catalog = current_database() AND table_schema = ? AND table_type = 'BASE TABLE' ORDER BY table_name";
+
+        let mut stmt = self
+            .conn
+            .prepare(query)
+            .map_err(|e| Error::motherduck_query(&self.config.schema, "List tables failed", e))?;
+
+        let tables: Vec<String> = stmt
+            .query_map([&self.config.schema], |row| row.get(0))
+            .map_err(|e| Error::motherduck_query(&self.config.schema, "List tables failed", e))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(tables)
+    }
+
+    /// Export a whole table to a local file with `COPY ... TO`.
+    ///
+    /// Returns the number of rows written.
+    #[instrument(skip(self))]
+    pub fn export_table(&self, table: &str, format: ExportFormat, path: &str) -> Result<usize> {
+        let source = format!(
+            "SELECT * FROM {}",
+            quote_qualified_identifier(&self.qualify(table))
+        );
+        self.copy_to(table, &source, format, path)
+    }
+
+    /// Export the result of a query to a local file with `COPY ... TO`.
+    ///
+    /// Returns the number of rows written.
+    #[instrument(skip(self))]
+    pub fn export_query(&self, sql: &str, format: ExportFormat, path: &str) -> Result<usize> {
+        self.copy_to("", sql, format, path)
+    }
+
+    fn copy_to(
+        &self,
+        table: &str,
+        source: &str,
+        format: ExportFormat,
+        path: &str,
+    ) -> Result<usize> {
+        self.metrics.record_md_query();
+        let rows = self
+            .conn
+            .execute(&copy_statement(source, format, path), [])
+            .map_err(|e| Error::motherduck_query(table, format!("Export to {} failed", path), e))?;
+        info!("Exported {} rows to {} ({})", rows, path, format);
+        Ok(rows)
+    }
+
+    /// Execute raw SQL.
+    pub fn execute(&self, sql: &str) -> Result<usize> {
+        self.metrics.record_md_query();
+        self.conn
+            .execute(sql, [])
+            .map_err(|e| Error::motherduck_query("", "Execute failed", e))
     }
 
     /// Execute batch SQL.
     pub fn execute_batch(&self, sql: &str) -> Result<()> {
+        self.metrics.record_md_query();
         self.conn
             .execute_batch(sql)
             .map_err(|e| Error::motherduck_query("", "Execute batch failed", e))
@@ -353,6 +1324,197 @@ impl MotherDuckClient {
     }
 }
 
+impl SyncSink for MotherDuckClient {
+    fn ping(&self) -> Result<()> {
+        self.ping()
+    }
+
+    /// Open a fresh connection with the same config, keeping metrics and the
+    /// rate limit.
+    fn reconnect(&mut self) -> Result<()> {
+        self.conn = MotherDuckClient::connect(self.config.clone())?.conn;
+        Ok(())
+    }
+
+    fn statement_watchdog(&self) -> Option<StatementWatchdog> {
+        self.statement_watchdog()
+    }
+
+    fn table_exists(&self, table: &str) -> Result<bool> {
+        self.table_exists(table)
+    }
+
+    fn ensure_table(
+        &self,
+        table: &str,
+        columns: &[IntrospectedColumn],
+        primary_key: &[String],
+        indexes: &[Index],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<()> {
+        self.create_table_from_schema(table, columns, primary_key, indexes, type_overrides)
+    }
+
+    fn reconcile_schema(
+        &self,
+        table: &str,
+        columns: &[IntrospectedColumn],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<String>> {
+        self.reconcile_schema(table, columns, type_overrides)
+    }
+
+    fn check_schema_compatibility(
+        &self,
+        table: &str,
+        columns: &[IntrospectedColumn],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<SchemaMismatch>> {
+        self.check_schema_compatibility(table, columns, type_overrides)
+    }
+
+    fn upsert(&self, mapping: &TableMapping, rows: &[HashMap<String, JsonValue>]) -> Result<usize> {
+        self.upsert_rows(mapping, rows)
+    }
+
+    fn batch_upsert(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+        batch_size: usize,
+        scope: TransactionScope,
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<usize> {
+        self.batch_upsert(mapping, rows, batch_size, scope, progress)
+    }
+
+    fn append_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+    ) -> Result<usize> {
+        self.append_rows(mapping, rows)
+    }
+
+    fn load_csv(&self, mapping: &TableMapping, columns: &[String], path: &str) -> Result<usize> {
+        self.load_csv(mapping, columns, path)
+    }
+
+    fn delete_rows(&self, mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<usize> {
+        self.delete_rows(mapping, keys)
+    }
+
+    fn delete_missing(
+        &self,
+        mapping: &TableMapping,
+        live_keys: &[Vec<JsonValue>],
+    ) -> Result<usize> {
+        self.delete_missing(mapping, live_keys)
+    }
+
+    fn count_rows(&self, table: &str) -> Result<i64> {
+        self.count_rows(table)
+    }
+
+    fn drop_table(&self, table: &str) -> Result<()> {
+        self.drop_table(table)
+    }
+
+    fn swap_table(&self, staging: &str, target: &str, indexes: &[Index]) -> Result<()> {
+        self.swap_table(staging, target, indexes)
+    }
+
+    fn begin(&self, table: &str) -> Result<()> {
+        self.begin(table)
+    }
+
+    fn commit(&self, table: &str) -> Result<()> {
+        self.commit(table)
+    }
+
+    fn rollback(&self) {
+        self.rollback()
+    }
+
+    fn ensure_schema(&self) -> Result<()> {
+        self.ensure_schema()
+    }
+
+    fn create_analytics_tables(&self, tables: &AnalyticsTablesConfig) -> Result<()> {
+        self.create_analytics_tables(tables)
+    }
+
+    fn ensure_sync_metadata(&self) -> Result<()> {
+        self.ensure_sync_metadata()
+    }
+
+    fn get_watermark(&self, table: &str) -> Result<Option<JsonValue>> {
+        self.get_watermark(table)
+    }
+
+    fn set_watermark(
+        &self,
+        table: &str,
+        watermark: &JsonValue,
+        records_synced: usize,
+        sync_mode: &str,
+    ) -> Result<()> {
+        self.set_watermark(table, watermark, records_synced, sync_mode)
+    }
+
+    fn record_sync(&self, table: &str, records_synced: usize, sync_mode: &str) -> Result<()> {
+        self.record_sync(table, records_synced, sync_mode)
+    }
+
+    fn analyze_table(&self, table: &str) -> Result<()> {
+        self.analyze_table(table)
+    }
+
+    fn checkpoint(&self) -> Result<()> {
+        self.checkpoint()
+    }
+
+    fn run_post_sql(&self, mapping: &TableMapping) -> Result<()> {
+        self.run_post_sql(mapping)
+    }
+
+    fn preview_writes(
+        &self,
+        mapping: &TableMapping,
+        columns: &[IntrospectedColumn],
+        indexes: &[Index],
+        create: bool,
+    ) -> Result<Vec<String>> {
+        self.preview_writes(mapping, columns, indexes, create)
+    }
+
+    fn execute(&self, sql: &str) -> Result<usize> {
+        self.execute(sql)
+    }
+}
+
+/// Run `SET name = 'value'` for each of `motherduck.pragmas`.
+fn apply_pragmas(conn: &Connection, pragmas: &BTreeMap<String, String>) -> Result<()> {
+    for (name, value) in pragmas {
+        let sql = format!("SET {} = '{}'", name, value.replace('\'', "''"));
+        debug!("Applying DuckDB setting: {}", sql);
+        conn.execute_batch(&sql).map_err(|e| {
+            Error::motherduck_connection(format!("Failed to apply setting {}", name), e)
+        })?;
+    }
+    Ok(())
+}
+
+/// Build a `COPY (source) TO 'path' (options)` statement.
+fn copy_statement(source: &str, format: ExportFormat, path: &str) -> String {
+    format!(
+        "COPY ({}) TO '{}' ({})",
+        source.trim().trim_end_matches(';'),
+        path.replace('\'', "''"),
+        format.copy_options()
+    )
+}
+
 /// Convert JSON value to SQL string representation.
 #[allow(dead_code)]
 fn json_to_sql_string(value: &JsonValue) -> String {
@@ -365,21 +1527,334 @@ fn json_to_sql_string(value: &JsonValue) -> String {
     }
 }
 
-/// Convert JSON value to SQL literal (properly escaped for direct SQL insertion).
-fn json_to_sql_literal(value: &JsonValue) -> String {
+/// Convert JSON value to a typed DuckDB value for parameter binding.
+fn json_to_duckdb_value(value: &JsonValue) -> DuckValue {
     match value {
-        JsonValue::Null => "NULL".to_string(),
-        JsonValue::Bool(b) => if *b { "TRUE" } else { "FALSE" }.to_string(),
-        JsonValue::Number(n) => n.to_string(),
-        JsonValue::String(s) => {
-            // Escape single quotes by doubling them
-            let escaped = s.replace('\'', "''");
-            format!("'{}'", escaped)
+        JsonValue::Null => DuckValue::Null,
+        JsonValue::Bool(b) => DuckValue::Boolean(*b),
+        JsonValue::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                DuckValue::BigInt(i)
+            } else if let Some(u) = n.as_u64() {
+                DuckValue::UBigInt(u)
+            } else {
+                DuckValue::Double(n.as_f64().unwrap_or_default())
+            }
+        }
+        JsonValue::String(s) => DuckValue::Text(s.clone()),
+        // Arrays go in as JSON text, which DuckDB casts to the target LIST type
+        JsonValue::Array(_) | JsonValue::Object(_) => DuckValue::Text(value.to_string()),
+    }
+}
+
+/// Source columns whose mapped DuckDB type differs from the `existing` target type.
+fn schema_mismatches(
+    existing: &HashMap<String, String>,
+    columns: &[crate::schema::IntrospectedColumn],
+    type_overrides: &HashMap<String, String>,
+) -> Vec<SchemaMismatch> {
+    let existing: HashMap<String, &String> = existing
+        .iter()
+        .map(|(name, data_type)| (name.to_lowercase(), data_type))
+        .collect();
+
+    columns
+        .iter()
+        .filter_map(|col| {
+            let target_type = existing.get(&col.name.to_lowercase())?;
+            let source_type = col.column_type(type_overrides).to_duckdb_ddl_fragment();
+            (base_duckdb_type(target_type) != base_duckdb_type(&source_type)).then(|| {
+                SchemaMismatch {
+                    column: col.name.clone(),
+                    target_type: target_type.to_string(),
+                    source_type,
+                }
+            })
+        })
+        .collect()
+}
+
+/// A DuckDB type name without parameters and with aliases resolved.
+///
+/// `DECIMAL(10, 2)` becomes `DECIMAL` and `TIMESTAMPTZ` becomes
+/// `TIMESTAMP WITH TIME ZONE`; list types keep their `[]` suffix.
+fn base_duckdb_type(data_type: &str) -> String {
+    let normalized = data_type.trim().to_uppercase();
+    if let Some(element) = normalized.strip_suffix("[]") {
+        return format!("{}[]", base_duckdb_type(element));
+    }
+    let base = normalized
+        .split_once('(')
+        .map_or(normalized.as_str(), |(base, _)| base)
+        .trim();
+    match base {
+        "BOOL" | "LOGICAL" => "BOOLEAN",
+        "INT2" | "SHORT" => "SMALLINT",
+        "INT" | "INT4" | "SIGNED" => "INTEGER",
+        "INT8" | "LONG" => "BIGINT",
+        "REAL" | "FLOAT4" => "FLOAT",
+        "FLOAT8" => "DOUBLE",
+        "NUMERIC" => "DECIMAL",
+        "TEXT" | "STRING" | "CHAR" | "BPCHAR" => "VARCHAR",
+        "TIMESTAMPTZ" => "TIMESTAMP WITH TIME ZONE",
+        "TIMETZ" => "TIME WITH TIME ZONE",
+        "BYTEA" | "BINARY" | "VARBINARY" => "BLOB",
+        other => other,
+    }
+    .to_string()
+}
+
+/// The cast for a temporal DuckDB column type, if `data_type` is one.
+///
+/// Temporal values arrive as ISO 8601 strings; casting the bound text makes
+/// DuckDB parse them (offsets included) instead of relying on implicit casts.
+fn temporal_cast(data_type: &str) -> Option<&'static str> {
+    match data_type.to_uppercase().as_str() {
+        "DATE" => Some("DATE"),
+        "TIME" => Some("TIME"),
+        "TIME WITH TIME ZONE" | "TIMETZ" => Some("TIMETZ"),
+        "TIMESTAMP" => Some("TIMESTAMP"),
+        "TIMESTAMP WITH TIME ZONE" | "TIMESTAMPTZ" => Some("TIMESTAMPTZ"),
+        "TIMESTAMP_S" => Some("TIMESTAMP_S"),
+        "TIMESTAMP_MS" => Some("TIMESTAMP_MS"),
+        "TIMESTAMP_NS" => Some("TIMESTAMP_NS"),
+        _ => None,
+    }
+}
+
+/// Build the target table for `columns`, in their (ordinal) order.
+///
+/// The sync flag column and indexes touching it are left out; columns in
+/// `type_overrides` get that DuckDB type instead of the inferred one.
+fn table_from_schema(
+    target_table: &str,
+    columns: &[crate::schema::IntrospectedColumn],
+    primary_key: &[String],
+    indexes: &[Index],
+    type_overrides: &HashMap<String, String>,
+) -> Table {
+    use crate::schema::Column;
+
+    let mut table = Table::new(target_table);
+
+    for col in columns {
+        // Skip the sync flag column - it's internal to PostgreSQL
+        if col.name == "synced_to_motherduck" {
+            continue;
+        }
+
+        let column =
+            Column::new(col.name.clone(), col.column_type(type_overrides)).nullable(col.nullable);
+
+        table.add_column(column);
+    }
+
+    if !primary_key.is_empty() {
+        table.set_primary_key(primary_key.to_vec());
+    }
+
+    for index in indexes {
+        if index.columns.iter().any(|c| c == "synced_to_motherduck") {
+            continue;
         }
-        JsonValue::Array(_) | JsonValue::Object(_) => {
-            // JSON values need to be escaped and quoted
-            let json_str = value.to_string().replace('\'', "''");
-            format!("'{}'", json_str)
+        table.add_index(index.clone());
+    }
+
+    table
+}
+
+/// Order a row's columns like the target table (`table_order`, ordinal).
+///
+/// Names match case-insensitively, as in DuckDB. Columns the target doesn't
+/// have go last, alphabetically, so the statement still names them and fails
+/// loudly.
+fn insert_columns<'a>(
+    row_columns: impl Iterator<Item = &'a String>,
+    table_order: &[&str],
+) -> Vec<&'a String> {
+    let position = |column: &str| {
+        table_order
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(column))
+            .unwrap_or(usize::MAX)
+    };
+    let mut columns: Vec<&String> = row_columns.collect();
+    columns.sort_by(|a, b| position(a).cmp(&position(b)).then_with(|| a.cmp(b)));
+    columns
+}
+
+/// Columns a write of `row` supplies: the row's own plus `column_defaults`.
+fn written_columns<'a>(
+    mapping: &'a TableMapping,
+    row: &'a HashMap<String, JsonValue>,
+) -> Vec<&'a String> {
+    row.keys()
+        .chain(
+            mapping
+                .column_defaults
+                .keys()
+                .filter(|c| !row.contains_key(*c)),
+        )
+        .collect()
+}
+
+/// The value written to `column`: the row's, else its `column_defaults` entry.
+///
+/// `bytea` values arrive base64-encoded; for a `BLOB` column in `types` they
+/// are decoded back to bytes.
+fn column_value(
+    mapping: &TableMapping,
+    row: &HashMap<String, JsonValue>,
+    column: &str,
+    types: &HashMap<String, String>,
+) -> DuckValue {
+    match row
+        .get(column)
+        .or_else(|| mapping.column_defaults.get(column))
+    {
+        Some(JsonValue::String(s))
+            if types
+                .get(column)
+                .is_some_and(|t| t.eq_ignore_ascii_case("BLOB")) =>
+        {
+            STANDARD
+                .decode(s)
+                .map(DuckValue::Blob)
+                .unwrap_or_else(|_| DuckValue::Text(s.clone()))
+        }
+        Some(value) => json_to_duckdb_value(value),
+        None => DuckValue::Null,
+    }
+}
+
+/// Build the prepared statement that upserts one row of `columns`.
+///
+/// Values are always bound as parameters, never interpolated into the SQL text.
+/// Placeholders for temporal columns in `types` are wrapped in a cast (see
+/// [`temporal_cast`]). With [`UpsertStrategy::OnConflictUpdate`] only the non-key
+/// `columns` are updated on conflict, so target columns the sync doesn't write
+/// keep their values.
+fn upsert_statement(
+    table: &str,
+    mapping: &TableMapping,
+    columns: &[&String],
+    types: &HashMap<String, String>,
+) -> String {
+    insert_statement(table, columns, types, upsert_clauses(mapping, columns))
+}
+
+/// Build a single-row insert of `columns` into `table` with the given insert
+/// verb and trailing conflict clause.
+fn insert_statement(
+    table: &str,
+    columns: &[&String],
+    types: &HashMap<String, String>,
+    (insert, conflict): (&str, String),
+) -> String {
+    let table = quote_qualified_identifier(table);
+    let col_names = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = columns
+        .iter()
+        .map(|c| match types.get(*c).and_then(|t| temporal_cast(t)) {
+            Some(cast) => format!("CAST(? AS {})", cast),
+            None => "?".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{} {} ({}) VALUES ({}){}",
+        insert, table, col_names, placeholders, conflict
+    )
+}
+
+/// Build the prepared statement that deletes one row of `table` by its primary key.
+fn delete_statement(table: &str, mapping: &TableMapping) -> String {
+    let matches = mapping
+        .primary_key
+        .iter()
+        .map(|c| format!("{} = ?", quote_identifier(c)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    format!(
+        "DELETE FROM {} WHERE {}",
+        quote_qualified_identifier(table),
+        matches
+    )
+}
+
+/// Build the statement that loads `columns` of a CSV file into `table`.
+///
+/// With `upsert` conflicts are resolved like in [`upsert_statement`]; otherwise
+/// it is a plain insert.
+fn load_csv_statement(
+    table: &str,
+    mapping: &TableMapping,
+    columns: &[String],
+    path: &str,
+    upsert: bool,
+) -> String {
+    let table = quote_qualified_identifier(table);
+    let col_names = columns
+        .iter()
+        .map(|c| quote_identifier(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let source = format!(
+        "SELECT {} FROM read_csv('{}', header = true, all_varchar = true, allow_quoted_nulls = false)",
+        col_names,
+        path.replace('\'', "''")
+    );
+
+    if !upsert {
+        return format!("INSERT INTO {} ({}) {}", table, col_names, source);
+    }
+    let columns: Vec<&String> = columns.iter().collect();
+    let (insert, conflict) = upsert_clauses(mapping, &columns);
+    format!(
+        "{} {} ({}) {}{}",
+        insert, table, col_names, source, conflict
+    )
+}
+
+/// The insert verb and `ON CONFLICT` clause for the mapping's [`UpsertStrategy`].
+///
+/// Tables with `row_hash` always update on conflict, and only rows whose
+/// stored hash differs from the incoming one.
+fn upsert_clauses(mapping: &TableMapping, columns: &[&String]) -> (&'static str, String) {
+    match mapping.upsert_strategy {
+        UpsertStrategy::Replace if !mapping.row_hash => ("INSERT OR REPLACE INTO", String::new()),
+        _ => {
+            let conflict_target = mapping
+                .primary_key
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let updates = columns
+                .iter()
+                .filter(|c| !mapping.primary_key.contains(c))
+                .map(|c| format!("{col} = excluded.{col}", col = quote_identifier(c)))
+                .collect::<Vec<_>>();
+            let action = if updates.is_empty() {
+                "NOTHING".to_string()
+            } else if mapping.row_hash {
+                format!(
+                    "UPDATE SET {} WHERE {col} IS DISTINCT FROM excluded.{col}",
+                    updates.join(", "),
+                    col = quote_identifier(ROW_HASH_COLUMN)
+                )
+            } else {
+                format!("UPDATE SET {}", updates.join(", "))
+            };
+            (
+                "INSERT INTO",
+                format!(" ON CONFLICT ({}) DO {}", conflict_target, action),
+            )
         }
     }
 }
@@ -388,6 +1863,15 @@ fn json_to_sql_literal(value: &JsonValue) -> String {
 mod tests {
     use super::*;
 
+    fn in_memory_client() -> MotherDuckClient {
+        MotherDuckClient {
+            conn: Connection::open_in_memory().unwrap(),
+            config: MotherDuckConfig::default(),
+            metrics: Arc::new(Metrics::new()),
+            limiter: None,
+        }
+    }
+
     #[test]
     fn test_json_to_sql_string() {
         assert_eq!(json_to_sql_string(&JsonValue::Null), "NULL");
@@ -398,4 +1882,806 @@ mod tests {
             "test"
         );
     }
+
+    #[test]
+    fn test_upsert_statement() {
+        let mapping = TableMapping::builder()
+            .source_table("user_roles")
+            .primary_key(["user_id", "role_id"])
+            .build()
+            .unwrap();
+        let (user_id, role_id, granted_at) = (
+            "user_id".to_string(),
+            "role_id".to_string(),
+            "granted_at".to_string(),
+        );
+        let columns = [&granted_at, &role_id, &user_id];
+        let types = HashMap::new();
+
+        assert_eq!(
+            upsert_statement("user_roles", &mapping, &columns, &types),
+            r#"INSERT OR REPLACE INTO "user_roles" ("granted_at", "role_id", "user_id") VALUES (?, ?, ?)"#
+        );
+
+        let mapping = TableMapping {
+            upsert_strategy: UpsertStrategy::OnConflictUpdate,
+            ..mapping
+        };
+        assert_eq!(
+            upsert_statement("user_roles", &mapping, &columns, &types),
+            r#"INSERT INTO "user_roles" ("granted_at", "role_id", "user_id") VALUES (?, ?, ?) ON CONFLICT ("user_id", "role_id") DO UPDATE SET "granted_at" = excluded."granted_at""#
+        );
+        assert!(
+            upsert_statement("user_roles", &mapping, &[&role_id, &user_id], &types)
+                .ends_with("DO NOTHING")
+        );
+    }
+
+    #[test]
+    fn test_columns_follow_source_order() {
+        use crate::schema::IntrospectedColumn;
+
+        let column = |name: &str, pg_type: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: pg_type.to_string(),
+            nullable: true,
+            default: None,
+            is_primary_key: false,
+        };
+        // As introspected: ordinal position, not alphabetical
+        let source = [
+            column("id", "bigint"),
+            column("title", "text"),
+            column("synced_to_motherduck", "boolean"),
+            column("created_at", "timestamptz"),
+            column("author_id", "bigint"),
+        ];
+
+        let table = table_from_schema("posts", &source, &["id".to_string()], &[], &HashMap::new());
+        let ddl = table.to_duckdb_ddl();
+        let positions: Vec<usize> = ["\"id\"", "\"title\"", "\"created_at\"", "\"author_id\""]
+            .iter()
+            .map(|c| ddl.find(c).unwrap())
+            .collect();
+        assert!(positions.is_sorted(), "{}", ddl);
+        assert!(!ddl.contains("synced_to_motherduck"));
+
+        // Rows come as maps; the insert list follows the created table
+        let order: Vec<&str> = table.columns.iter().map(|c| c.name.as_str()).collect();
+        let row: HashMap<String, JsonValue> = ["author_id", "created_at", "ID", "title", "extra"]
+            .into_iter()
+            .map(|c| (c.to_string(), JsonValue::Null))
+            .collect();
+        assert_eq!(
+            insert_columns(row.keys(), &order),
+            ["ID", "title", "created_at", "author_id", "extra"]
+        );
+    }
+
+    #[test]
+    fn test_preview_writes() {
+        use crate::schema::IntrospectedColumn;
+
+        let client = in_memory_client();
+        let column = |name: &str, pg_type: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: pg_type.to_string(),
+            nullable: true,
+            default: None,
+            is_primary_key: false,
+        };
+        let columns = [
+            column("id", "bigint"),
+            column("seen_at", "timestamptz"),
+            column("synced_to_motherduck", "boolean"),
+        ];
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+
+        let sql = client
+            .preview_writes(&mapping, &columns, &[], true)
+            .unwrap();
+        assert_eq!(sql.len(), 2);
+        assert!(sql[0].starts_with("CREATE TABLE IF NOT EXISTS \"main\".\"events\""));
+        assert_eq!(
+            sql[1],
+            r#"INSERT OR REPLACE INTO "main"."events" ("id", "seen_at") VALUES (?, CAST(? AS TIMESTAMPTZ))"#
+        );
+        // Previewing runs nothing
+        assert!(!client.table_exists("events").unwrap());
+
+        // An existing table without a conflict target gets plain inserts
+        client
+            .execute("CREATE TABLE events (seen_at TIMESTAMPTZ, id BIGINT)")
+            .unwrap();
+        assert_eq!(
+            client
+                .preview_writes(&mapping, &columns, &[], true)
+                .unwrap(),
+            [r#"INSERT INTO "main"."events" ("seen_at", "id") VALUES (CAST(? AS TIMESTAMPTZ), ?)"#]
+        );
+    }
+
+    #[test]
+    fn test_delete_rows() {
+        let client = in_memory_client();
+        client
+            .execute_batch(
+                "CREATE TABLE roles (user_id INTEGER, role_id VARCHAR, PRIMARY KEY (user_id, role_id));",
+            )
+            .unwrap();
+        let mapping = TableMapping::builder()
+            .source_table("user_roles")
+            .target_table("roles")
+            .primary_key(["user_id", "role_id"])
+            .map_column("role_id", "role")
+            .build()
+            .unwrap();
+
+        // Rows are deleted by the key columns they were upserted under
+        let rows: Vec<_> = [(1, "admin"), (1, "viewer"), (2, "admin")]
+            .into_iter()
+            .map(|(user, role)| {
+                HashMap::from([
+                    ("user_id".to_string(), JsonValue::from(user)),
+                    ("role_id".to_string(), JsonValue::from(role)),
+                ])
+            })
+            .collect();
+        assert_eq!(client.upsert_rows(&mapping, &rows).unwrap(), 3);
+        let keys = [
+            vec![JsonValue::from(1), JsonValue::from("viewer")],
+            vec![JsonValue::from(3), JsonValue::from("admin")],
+        ];
+        assert_eq!(client.delete_rows(&mapping, &keys).unwrap(), 1);
+        assert_eq!(client.count_rows("roles").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_upsert_statement_casts_temporal_columns() {
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+        let (id, day, at) = ("id".to_string(), "day".to_string(), "at".to_string());
+        let types: HashMap<String, String> = [
+            ("id", "INTEGER"),
+            ("day", "DATE"),
+            ("at", "TIMESTAMP WITH TIME ZONE"),
+        ]
+        .into_iter()
+        .map(|(c, t)| (c.to_string(), t.to_string()))
+        .collect();
+
+        assert_eq!(
+            upsert_statement("events", &mapping, &[&at, &day, &id], &types),
+            r#"INSERT OR REPLACE INTO "events" ("at", "day", "id") VALUES (CAST(? AS TIMESTAMPTZ), CAST(? AS DATE), ?)"#
+        );
+        assert_eq!(temporal_cast("timestamp"), Some("TIMESTAMP"));
+        assert_eq!(temporal_cast("VARCHAR"), None);
+    }
+
+    #[test]
+    fn test_load_csv_statement() {
+        let mapping = TableMapping::builder()
+            .source_table("users")
+            .primary_key(["id"])
+            .upsert_strategy(UpsertStrategy::OnConflictUpdate)
+            .build()
+            .unwrap();
+        let columns = vec!["id".to_string(), "name".to_string()];
+
+        assert_eq!(
+            load_csv_statement("users", &mapping, &columns, "/tmp/it's.csv", true),
+            "INSERT INTO \"users\" (\"id\", \"name\") SELECT \"id\", \"name\" FROM \
+             read_csv('/tmp/it''s.csv', header = true, all_varchar = true, allow_quoted_nulls = false) \
+             ON CONFLICT (\"id\") DO UPDATE SET \"name\" = excluded.\"name\""
+        );
+        assert!(
+            load_csv_statement("users", &mapping, &columns, "/tmp/users.csv", false)
+                .starts_with("INSERT INTO \"users\" (\"id\", \"name\") SELECT")
+        );
+    }
+
+    #[test]
+    fn test_row_hash_upsert() {
+        let client = in_memory_client();
+        client
+            .execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR, _row_hash VARCHAR)")
+            .unwrap();
+        let mapping = TableMapping::builder()
+            .source_table("users")
+            .primary_key_column("id")
+            .row_hash(true)
+            .build()
+            .unwrap();
+        let id = "id".to_string();
+        let name = "name".to_string();
+        let hash = ROW_HASH_COLUMN.to_string();
+        assert!(
+            upsert_statement("users", &mapping, &[&id, &name, &hash], &HashMap::new()).ends_with(
+                r#"ON CONFLICT ("id") DO UPDATE SET "name" = excluded."name", "_row_hash" = excluded."_row_hash" WHERE "_row_hash" IS DISTINCT FROM excluded."_row_hash""#
+            )
+        );
+
+        let row = |name: &str, hash: &str| {
+            HashMap::from([
+                ("id".to_string(), JsonValue::from(1)),
+                ("name".to_string(), JsonValue::from(name)),
+                (ROW_HASH_COLUMN.to_string(), JsonValue::from(hash)),
+            ])
+        };
+        let name_of_1 = || -> String {
+            client
+                .connection()
+                .query_row("SELECT name FROM users WHERE id = 1", [], |r| r.get(0))
+                .unwrap()
+        };
+        assert_eq!(
+            client.upsert_rows(&mapping, &[row("ann", "h1")]).unwrap(),
+            1
+        );
+
+        // Same hash: the stored row is left alone, and not counted as written
+        assert_eq!(
+            client.upsert_rows(&mapping, &[row("bob", "h1")]).unwrap(),
+            0
+        );
+        assert_eq!(name_of_1(), "ann");
+
+        assert_eq!(
+            client.upsert_rows(&mapping, &[row("bob", "h2")]).unwrap(),
+            1
+        );
+        assert_eq!(name_of_1(), "bob");
+    }
+
+    #[test]
+    fn test_post_sql() {
+        let client = in_memory_client();
+        client
+            .execute("CREATE TABLE people (id INTEGER PRIMARY KEY, first VARCHAR, last VARCHAR, full_name VARCHAR)")
+            .unwrap();
+        let mapping = |post_sql: &str| {
+            TableMapping::builder()
+                .source_table("people")
+                .primary_key_column("id")
+                .post_sql(post_sql)
+                .build()
+                .unwrap()
+        };
+        let row = HashMap::from([
+            ("id".to_string(), JsonValue::from(1)),
+            ("first".to_string(), JsonValue::from("Ada")),
+            ("last".to_string(), JsonValue::from("Lovelace")),
+        ]);
+
+        let derive = mapping("UPDATE {{target}} SET full_name = first || ' ' || last");
+        client
+            .batch_upsert(
+                &derive,
+                std::slice::from_ref(&row),
+                10,
+                TransactionScope::PerTable,
+                |_| {},
+            )
+            .unwrap();
+        let full_name: String = client
+            .connection()
+            .query_row("SELECT full_name FROM people WHERE id = 1", [], |r| {
+                r.get(0)
+            })
+            .unwrap();
+        assert_eq!(full_name, "Ada Lovelace");
+
+        // A failing post_sql rolls back the table's rows with it
+        let failing = mapping("UPDATE {{target}} SET missing = 1");
+        let mut changed = row.clone();
+        changed.insert("first".to_string(), JsonValue::from("Augusta"));
+        assert!(
+            client
+                .batch_upsert(&failing, &[changed], 10, TransactionScope::PerTable, |_| {})
+                .is_err()
+        );
+        let first: String = client
+            .connection()
+            .query_row("SELECT first FROM people WHERE id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(first, "Ada");
+    }
+
+    #[test]
+    fn test_copy_statement() {
+        assert_eq!(
+            copy_statement(
+                "SELECT * FROM \"daily_stats\"",
+                ExportFormat::Parquet,
+                "./daily.parquet"
+            ),
+            r#"COPY (SELECT * FROM "daily_stats") TO './daily.parquet' (FORMAT PARQUET)"#
+        );
+        assert_eq!(
+            copy_statement("SELECT 1;", ExportFormat::Csv, "it's.csv"),
+            "COPY (SELECT 1) TO 'it''s.csv' (FORMAT CSV, HEADER)"
+        );
+    }
+
+    #[test]
+    fn test_list_tables() {
+        let client = in_memory_client();
+        client
+            .execute_batch("CREATE TABLE users (id BIGINT); CREATE TABLE events (id BIGINT); CREATE VIEW active AS SELECT * FROM users; CREATE SCHEMA other; CREATE TABLE other.logs (id BIGINT)")
+            .unwrap();
+
+        assert_eq!(client.list_tables().unwrap(), ["events", "users"]);
+    }
+
+    #[test]
+    fn test_pragmas_applied_on_connect() {
+        let mut config = MotherDuckConfig {
+            local_path: Some(":memory:".to_string()),
+            ..Default::default()
+        };
+        config
+            .pragmas
+            .insert("threads".to_string(), "2".to_string());
+        let client = MotherDuckClient::connect(config.clone()).unwrap();
+        let threads: String = client
+            .conn
+            .query_row("SELECT current_setting('threads')::VARCHAR", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(threads, "2");
+
+        config
+            .pragmas
+            .insert("no_such_setting".to_string(), "1".to_string());
+        assert!(MotherDuckClient::connect(config).is_err());
+    }
+
+    #[test]
+    fn test_case_insensitive_tables() {
+        let mut client = in_memory_client();
+        client
+            .execute("CREATE TABLE \"Events\" (id BIGINT PRIMARY KEY, Name VARCHAR)")
+            .unwrap();
+        assert!(!client.table_exists("events").unwrap());
+        assert!(!client.has_conflict_target("events").unwrap());
+
+        client.config.case_insensitive_tables = true;
+        assert!(client.table_exists("events").unwrap());
+        assert!(client.table_exists("main.EVENTS").unwrap());
+        assert!(client.has_conflict_target("events").unwrap());
+        assert_eq!(client.table_columns("events").unwrap(), ["id", "Name"]);
+    }
+
+    #[test]
+    fn test_configured_schema() {
+        use crate::schema::IntrospectedColumn;
+
+        let client = MotherDuckClient {
+            config: MotherDuckConfig {
+                schema: "analytics".to_string(),
+                ..Default::default()
+            },
+            ..in_memory_client()
+        };
+        client.ensure_schema().unwrap();
+        client.ensure_sync_metadata().unwrap();
+        // A same-named table in main must not be mistaken for the target
+        client
+            .execute("CREATE TABLE main.events (id BIGINT)")
+            .unwrap();
+
+        let columns = [IntrospectedColumn {
+            name: "id".to_string(),
+            pg_type: "bigint".to_string(),
+            nullable: false,
+            default: None,
+            is_primary_key: true,
+        }];
+        client
+            .create_table_from_schema(
+                "events",
+                &columns,
+                &["id".to_string()],
+                &[],
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert!(client.has_conflict_target("events").unwrap());
+        assert!(!client.has_conflict_target("main.events").unwrap());
+
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+        let row = HashMap::from([("id".to_string(), JsonValue::from(1))]);
+        client.upsert_rows(&mapping, &[row.clone(), row]).unwrap();
+        client.record_sync("events", 1, "full").unwrap();
+
+        assert_eq!(client.count_rows("events").unwrap(), 1);
+        assert_eq!(client.count_rows("main.events").unwrap(), 0);
+        assert_eq!(client.list_tables().unwrap(), ["events", "sync_metadata"]);
+    }
+
+    #[test]
+    fn test_analyze_table() {
+        let client = in_memory_client();
+        client
+            .execute_batch("CREATE TABLE stats (day DATE, visits INTEGER); INSERT INTO stats VALUES ('2024-01-01', 3)")
+            .unwrap();
+
+        client.analyze_table("stats").unwrap();
+        client.checkpoint().unwrap();
+        assert!(client.analyze_table("missing").is_err());
+    }
+
+    #[test]
+    fn test_export_table_csv() {
+        let client = in_memory_client();
+        client
+            .execute_batch("CREATE TABLE stats (day DATE, visits INTEGER); INSERT INTO stats VALUES ('2024-01-01', 3), ('2024-01-02', 5)")
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.csv");
+        let path = path.to_str().unwrap();
+
+        assert_eq!(
+            client
+                .export_table("stats", ExportFormat::Csv, path)
+                .unwrap(),
+            2
+        );
+        let csv = std::fs::read_to_string(path).unwrap();
+        assert!(csv.starts_with("day,visits"));
+        assert_eq!(csv.lines().count(), 3);
+    }
+
+    #[test]
+    fn test_schema_mismatches() {
+        use crate::schema::IntrospectedColumn;
+
+        let column = |name: &str, pg_type: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: pg_type.to_string(),
+            nullable: true,
+            default: None,
+            is_primary_key: false,
+        };
+        let existing: HashMap<String, String> = [
+            ("id", "BIGINT"),
+            ("price", "DECIMAL(18,3)"),
+            ("name", "VARCHAR"),
+            ("created_at", "TIMESTAMP WITH TIME ZONE"),
+            ("tags", "VARCHAR[]"),
+            ("Score", "INTEGER"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+
+        let columns = [
+            column("id", "bigint"),
+            // Precision differences don't count
+            column("price", "numeric(10,2)"),
+            column("name", "character varying(255)"),
+            column("created_at", "timestamptz"),
+            column("tags", "_text"),
+            // Names compare case-insensitively
+            column("score", "double precision"),
+            // Not in the target yet: left to reconcile_schema
+            column("email", "text"),
+        ];
+
+        assert_eq!(
+            schema_mismatches(&existing, &columns, &HashMap::new()),
+            vec![SchemaMismatch {
+                column: "score".to_string(),
+                target_type: "INTEGER".to_string(),
+                source_type: "DOUBLE".to_string(),
+            }]
+        );
+
+        // An override is what the target is compared against
+        let overrides = HashMap::from([("score".to_string(), "INTEGER".to_string())]);
+        assert!(schema_mismatches(&existing, &columns, &overrides).is_empty());
+    }
+
+    #[test]
+    fn test_json_to_duckdb_value() {
+        assert_eq!(json_to_duckdb_value(&JsonValue::Null), DuckValue::Null);
+        assert_eq!(
+            json_to_duckdb_value(&JsonValue::Bool(true)),
+            DuckValue::Boolean(true)
+        );
+        assert_eq!(
+            json_to_duckdb_value(&JsonValue::Number(42.into())),
+            DuckValue::BigInt(42)
+        );
+        assert_eq!(
+            json_to_duckdb_value(&serde_json::json!(1.5)),
+            DuckValue::Double(1.5)
+        );
+        assert_eq!(
+            json_to_duckdb_value(&serde_json::json!({"a": 1})),
+            DuckValue::Text(r#"{"a":1}"#.into())
+        );
+    }
+
+    #[test]
+    fn test_upsert_rows_binds_values() {
+        let client = in_memory_client();
+        client
+            .execute("CREATE TABLE x (id INTEGER PRIMARY KEY, body VARCHAR)")
+            .unwrap();
+
+        let mapping = TableMapping::builder()
+            .source_table("x")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+
+        let payload = "'); DROP TABLE x; --";
+        let mut row = HashMap::new();
+        row.insert("id".to_string(), JsonValue::Number(1.into()));
+        row.insert("body".to_string(), JsonValue::String(payload.into()));
+
+        assert_eq!(client.upsert_rows(&mapping, &[row]).unwrap(), 1);
+
+        let body: String = client
+            .connection()
+            .query_row("SELECT body FROM x WHERE id = 1", [], |r| r.get(0))
+            .unwrap();
+        assert_eq!(body, payload);
+    }
+
+    #[test]
+    fn test_blob_columns_decode_base64() {
+        let client = in_memory_client();
+        client
+            .execute_batch(
+                "CREATE TABLE files (id INTEGER PRIMARY KEY, body BLOB); CREATE TABLE log (body BLOB)",
+            )
+            .unwrap();
+        let mapping = |table: &str| {
+            TableMapping::builder()
+                .source_table(table)
+                .primary_key_column("id")
+                .build()
+                .unwrap()
+        };
+        let row = HashMap::from([
+            ("id".to_string(), JsonValue::from(1)),
+            ("body".to_string(), JsonValue::from("AP8K")),
+        ]);
+
+        client
+            .upsert_rows(&mapping("files"), std::slice::from_ref(&row))
+            .unwrap();
+        client.upsert_rows(&mapping("log"), &[row]).unwrap();
+        for table in ["files", "log"] {
+            let body: Vec<u8> = client
+                .connection()
+                .query_row(&format!("SELECT body FROM {}", table), [], |r| r.get(0))
+                .unwrap();
+            assert_eq!(body, [0x00, 0xff, 0x0a]);
+        }
+    }
+
+    #[test]
+    fn test_unwritten_not_null_columns() {
+        let client = in_memory_client();
+        client
+            .execute_batch(
+                "CREATE TABLE k (id INTEGER PRIMARY KEY, region VARCHAR NOT NULL, seen INTEGER NOT NULL DEFAULT 0);
+                 CREATE TABLE a (id INTEGER, region VARCHAR NOT NULL, seen INTEGER NOT NULL DEFAULT 0);",
+            )
+            .unwrap();
+        let row = HashMap::from([("id".to_string(), JsonValue::from(1))]);
+
+        let mapping = TableMapping::builder()
+            .source_table("k")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+        let err = client
+            .upsert_rows(&mapping, std::slice::from_ref(&row))
+            .unwrap_err();
+        assert!(err.to_string().contains("Column region of k is NOT NULL"));
+
+        // Values are written under their source names, so a mapping doesn't
+        // fill the column it names
+        client
+            .execute_batch("CREATE TABLE m (id INTEGER PRIMARY KEY, area VARCHAR NOT NULL);")
+            .unwrap();
+        let mapping = TableMapping::builder()
+            .source_table("m")
+            .primary_key_column("id")
+            .map_column("region", "area")
+            .build()
+            .unwrap();
+        let mut mapped = row.clone();
+        mapped.insert("region".to_string(), JsonValue::from("eu"));
+        let err = client.upsert_rows(&mapping, &[mapped]).unwrap_err();
+        assert!(
+            err.to_string().contains("Column area of m is NOT NULL"),
+            "{}",
+            err
+        );
+
+        // column_defaults fill the column; DuckDB's DEFAULT fills the rest,
+        // on the upsert path and in place of the Appender alike
+        for table in ["k", "a"] {
+            let mapping = TableMapping::builder()
+                .source_table(table)
+                .primary_key_column("id")
+                .column_default("region", "eu")
+                .build()
+                .unwrap();
+            assert_eq!(
+                client
+                    .upsert_rows(&mapping, std::slice::from_ref(&row))
+                    .unwrap(),
+                1
+            );
+
+            let (region, seen): (String, i32) = client
+                .connection()
+                .query_row(&format!("SELECT region, seen FROM {}", table), [], |r| {
+                    Ok((r.get(0)?, r.get(1)?))
+                })
+                .unwrap();
+            assert_eq!((region.as_str(), seen), ("eu", 0));
+        }
+    }
+
+    #[test]
+    fn test_delete_missing_keeps_live_keys() {
+        let client = in_memory_client();
+        client
+            .execute_batch(
+                "CREATE TABLE r (a INTEGER, b VARCHAR, PRIMARY KEY (a, b));
+                 INSERT INTO r VALUES (1, 'x'), (1, 'y'), (2, 'x');",
+            )
+            .unwrap();
+
+        let mapping = TableMapping::builder()
+            .source_table("r")
+            .primary_key(["a", "b"])
+            .hard_delete(true)
+            .build()
+            .unwrap();
+
+        let live = vec![
+            vec![serde_json::json!(1), serde_json::json!("x")],
+            vec![serde_json::json!(2), serde_json::json!("x")],
+        ];
+        assert_eq!(client.delete_missing(&mapping, &live).unwrap(), 1);
+        assert_eq!(client.count_rows("r").unwrap(), 2);
+
+        // An empty key set never wipes the table
+        assert_eq!(client.delete_missing(&mapping, &[]).unwrap(), 0);
+        assert_eq!(client.count_rows("r").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_record_sync_keeps_watermark() {
+        let client = in_memory_client();
+        client.ensure_sync_metadata().unwrap();
+
+        client
+            .set_watermark("events", &serde_json::json!(42), 10, "incremental")
+            .unwrap();
+        client.record_sync("events", 3, "full").unwrap();
+        client.record_sync("users", 7, "incremental").unwrap();
+
+        let (records, mode): (i64, String) = client
+            .conn
+            .query_row(
+                "SELECT records_synced, sync_mode FROM sync_metadata WHERE table_name = 'events'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((records, mode.as_str()), (3, "full"));
+        assert_eq!(
+            client.get_watermark("events").unwrap(),
+            Some(serde_json::json!(42))
+        );
+        assert_eq!(client.get_watermark("users").unwrap(), None);
+    }
+
+    #[test]
+    fn test_swap_table() {
+        let client = in_memory_client();
+        client
+            .execute_batch(
+                "CREATE TABLE dims (id INTEGER PRIMARY KEY, name VARCHAR); \
+                 INSERT INTO dims VALUES (1, 'old'); \
+                 CREATE INDEX dims_name_idx ON dims (name); \
+                 CREATE TABLE dims__staging (id INTEGER PRIMARY KEY, name VARCHAR); \
+                 INSERT INTO dims__staging VALUES (2, 'new'), (3, 'newer');",
+            )
+            .unwrap();
+
+        let indexes = [Index::new("dims_name_idx", vec!["name".to_string()])];
+        client
+            .swap_table("dims__staging", "dims", &indexes)
+            .unwrap();
+
+        assert!(!client.table_exists("dims__staging").unwrap());
+        assert_eq!(client.count_rows("dims").unwrap(), 2);
+        // The primary key survives the rename
+        assert!(client.has_conflict_target("dims").unwrap());
+    }
+
+    #[test]
+    fn test_reconcile_schema_adds_new_columns() {
+        use crate::schema::IntrospectedColumn;
+
+        let client = in_memory_client();
+        client
+            .execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR)")
+            .unwrap();
+
+        let column = |name: &str, pg_type: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: pg_type.to_string(),
+            nullable: true,
+            default: None,
+            is_primary_key: false,
+        };
+        let source = vec![
+            column("id", "integer"),
+            column("Name", "text"),
+            column("signed_up", "date"),
+        ];
+
+        let added = client
+            .reconcile_schema("users", &source, &HashMap::new())
+            .unwrap();
+        assert_eq!(added, vec!["signed_up"]);
+        assert_eq!(
+            client.table_columns("users").unwrap(),
+            vec!["id", "name", "signed_up"]
+        );
+
+        // A second pass is a no-op
+        assert!(
+            client
+                .reconcile_schema("users", &source, &HashMap::new())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_create_table_creates_indexes() {
+        use crate::schema::{Column, ColumnType, Index};
+
+        let client = in_memory_client();
+        let mut table = Table::new("daily");
+        table.add_column(Column::new("id", ColumnType::Integer));
+        table.add_column(Column::new("date", ColumnType::Date));
+        table.add_index(Index::new("idx_daily_date", vec!["date".to_string()]));
+
+        client.create_table(&table).unwrap();
+
+        let count: i64 = client
+            .connection()
+            .query_row(
+                "SELECT COUNT(*) FROM duckdb_indexes() WHERE index_name = 'idx_daily_date'",
+                [],
+                |r| r.get(0),
+            )
+            .unwrap();
+        assert_eq!(count, 1);
+    }
 }
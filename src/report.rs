@@ -0,0 +1,146 @@
+//! Persisted sync reports.
+//!
+//! With `sync.report_path` set, every sync writes its [`SyncResult`] as JSON to
+//! a new timestamped file under that location: a local directory, or an
+//! `s3://bucket/prefix` with the `s3` feature. File names sort by time, so
+//! consecutive runs are easy to find and diff.
+
+use crate::error::{Error, Result};
+use crate::sync::SyncResult;
+use chrono::{DateTime, Utc};
+use std::path::Path;
+use tracing::debug;
+
+/// Write `result` under `location` and return where it was written.
+pub async fn write_report(location: &str, result: &SyncResult) -> Result<String> {
+    let body = serde_json::to_vec_pretty(result).map_err(|e| Error::Serialization {
+        message: "Failed to serialize sync report".into(),
+        source: Some(Box::new(e)),
+    })?;
+    let name = report_file_name(Utc::now());
+
+    match location.strip_prefix("s3://") {
+        Some(rest) => {
+            let (bucket, key) = s3_location(rest, &name)?;
+            put_s3(&bucket, &key, body).await?;
+            Ok(format!("s3://{}/{}", bucket, key))
+        }
+        None => {
+            tokio::fs::create_dir_all(location).await?;
+            let path = Path::new(location).join(&name);
+            tokio::fs::write(&path, body).await?;
+            debug!("Wrote sync report {}", path.display());
+            Ok(path.display().to_string())
+        }
+    }
+}
+
+/// `sync-report-<UTC time>.json`, with milliseconds so back-to-back runs differ.
+fn report_file_name(at: DateTime<Utc>) -> String {
+    format!("sync-report-{}.json", at.format("%Y%m%dT%H%M%S%.3fZ"))
+}
+
+/// Split `bucket/prefix` (the part after `s3://`) into the bucket and the
+/// object key for `name`.
+fn s3_location(rest: &str, name: &str) -> Result<(String, String)> {
+    let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+    if bucket.is_empty() {
+        return Err(Error::config(format!(
+            "Invalid report_path s3://{}: missing bucket",
+            rest
+        )));
+    }
+    let prefix = prefix.trim_matches('/');
+    let key = if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", prefix, name)
+    };
+    Ok((bucket.to_string(), key))
+}
+
+/// Upload `body` to `bucket`/`key` with credentials from the standard AWS
+/// environment (variables, profile, instance role).
+#[cfg(feature = "s3")]
+async fn put_s3(bucket: &str, key: &str, body: Vec<u8>) -> Result<()> {
+    let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+    let client = aws_sdk_s3::Client::new(&config);
+    client
+        .put_object()
+        .bucket(bucket)
+        .key(key)
+        .content_type("application/json")
+        .body(body.into())
+        .send()
+        .await
+        .map_err(|e| {
+            Error::Io(std::io::Error::other(format!(
+                "S3 upload to s3://{}/{} failed: {}",
+                bucket,
+                key,
+                aws_sdk_s3::error::DisplayErrorContext(e)
+            )))
+        })?;
+    debug!("Wrote sync report s3://{}/{}", bucket, key);
+    Ok(())
+}
+
+#[cfg(not(feature = "s3"))]
+async fn put_s3(bucket: &str, key: &str, _body: Vec<u8>) -> Result<()> {
+    Err(Error::config(format!(
+        "Cannot write report to s3://{}/{}: S3 reports require the `s3` feature",
+        bucket, key
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_report_file_name() {
+        let at = Utc.with_ymd_and_hms(2026, 3, 4, 5, 6, 7).unwrap();
+        assert_eq!(
+            report_file_name(at),
+            "sync-report-20260304T050607.000Z.json"
+        );
+    }
+
+    #[test]
+    fn test_s3_location() {
+        let name = "r.json";
+        assert_eq!(
+            s3_location("audit", name).unwrap(),
+            ("audit".to_string(), "r.json".to_string())
+        );
+        assert_eq!(
+            s3_location("audit/sync/reports/", name).unwrap(),
+            ("audit".to_string(), "sync/reports/r.json".to_string())
+        );
+        assert!(s3_location("/reports", name).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_write_local_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let location = dir.path().join("reports");
+        let result = SyncResult {
+            success: true,
+            mode: "full".into(),
+            tables: Default::default(),
+            duration_ms: 12,
+            completed_at: Utc::now().to_rfc3339(),
+            error: None,
+        };
+
+        let written = write_report(location.to_str().unwrap(), &result)
+            .await
+            .unwrap();
+        assert!(written.starts_with(location.to_str().unwrap()));
+        let content = std::fs::read_to_string(&written).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["mode"], "full");
+        assert_eq!(parsed["duration_ms"], 12);
+    }
+}
@@ -1,8 +1,22 @@
 //! Schema types and DDL generation for motherduck-supasync.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
 
+/// Quote a SQL identifier, escaping embedded double quotes.
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quote a possibly schema-qualified name such as `schema.table`, part by part.
+pub fn quote_qualified_identifier(name: &str) -> String {
+    name.split('.')
+        .map(quote_identifier)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
 /// Database schema definition.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
@@ -73,7 +87,10 @@ impl Table {
 
     /// Generate CREATE TABLE DDL for DuckDB/MotherDuck.
     pub fn to_duckdb_ddl(&self) -> String {
-        let mut ddl = format!("CREATE TABLE IF NOT EXISTS {} (\n", self.name);
+        let mut ddl = format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n",
+            quote_qualified_identifier(&self.name)
+        );
 
         let col_defs: Vec<String> = self
             .columns
@@ -81,8 +98,8 @@ impl Table {
             .map(|c| {
                 format!(
                     "    {} {}{}",
-                    c.name,
-                    c.column_type.to_duckdb(),
+                    quote_identifier(&c.name),
+                    c.column_type.to_duckdb_ddl_fragment(),
                     c.constraints_ddl()
                 )
             })
@@ -91,10 +108,12 @@ impl Table {
         ddl.push_str(&col_defs.join(",\n"));
 
         if !self.primary_key.is_empty() {
-            ddl.push_str(&format!(
-                ",\n    PRIMARY KEY ({})",
-                self.primary_key.join(", ")
-            ));
+            let pk_cols: Vec<String> = self
+                .primary_key
+                .iter()
+                .map(|c| quote_identifier(c))
+                .collect();
+            ddl.push_str(&format!(",\n    PRIMARY KEY ({})", pk_cols.join(", ")));
         }
 
         ddl.push_str("\n)");
@@ -177,7 +196,7 @@ impl Column {
 }
 
 /// Column types supported by both PostgreSQL and DuckDB.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ColumnType {
     /// Boolean
@@ -220,6 +239,8 @@ pub enum ColumnType {
     Json,
     /// Binary data
     Blob,
+    /// One-dimensional array of the element type (a DuckDB LIST)
+    Array(Box<ColumnType>),
 }
 
 impl ColumnType {
@@ -242,12 +263,43 @@ impl ColumnType {
             ColumnType::Uuid => "VARCHAR",
             ColumnType::Json => "JSON",
             ColumnType::Blob => "BLOB",
+            ColumnType::Array(_) => "LIST",
+        }
+    }
+
+    /// Convert to a DuckDB column type for DDL, including precision, scale and length.
+    pub fn to_duckdb_ddl_fragment(&self) -> String {
+        match self {
+            ColumnType::Decimal { precision, scale } => {
+                format!("DECIMAL({}, {})", precision, scale)
+            }
+            ColumnType::Varchar {
+                max_length: Some(len),
+            } => format!("VARCHAR({})", len),
+            ColumnType::Array(element) => format!("{}[]", element.to_duckdb_ddl_fragment()),
+            other => other.to_duckdb().to_string(),
         }
     }
 
     /// Parse from PostgreSQL type name.
+    ///
+    /// Type modifiers such as `numeric(10,2)` or `character varying(255)` are
+    /// kept. Numerics wider than DuckDB's 38-digit maximum become `Double`.
+    ///
+    /// Arrays (`_int4` or `integer[]`) become `Array` of the element type.
+    /// Multi-dimensional arrays (`integer[][]`) have no LIST equivalent here and
+    /// fall back to `Text`.
     pub fn from_postgres(pg_type: &str) -> Self {
         let normalized = pg_type.to_lowercase();
+        let element = normalized
+            .strip_suffix("[]")
+            .or_else(|| normalized.strip_prefix('_'));
+        if let Some(element) = element {
+            return match ColumnType::from_postgres(element) {
+                ColumnType::Array(_) => ColumnType::Text,
+                element => ColumnType::Array(Box::new(element)),
+            };
+        }
         match normalized.as_str() {
             "boolean" | "bool" => ColumnType::Boolean,
             "smallint" | "int2" => ColumnType::SmallInt,
@@ -264,20 +316,113 @@ impl ColumnType {
             "bytea" => ColumnType::Blob,
             "text" => ColumnType::Text,
             s if s.starts_with("character varying") || s.starts_with("varchar") => {
-                ColumnType::Varchar { max_length: None }
+                ColumnType::Varchar {
+                    max_length: type_modifiers(s).first().copied(),
+                }
+            }
+            s if s.starts_with("numeric") || s.starts_with("decimal") => {
+                match type_modifiers(s)[..] {
+                    [] => ColumnType::Decimal {
+                        precision: 38,
+                        scale: 9,
+                    },
+                    [precision, ..] if precision > 38 => ColumnType::Double,
+                    [precision] => ColumnType::Decimal {
+                        precision: precision as u8,
+                        scale: 0,
+                    },
+                    [precision, scale, ..] => ColumnType::Decimal {
+                        precision: precision as u8,
+                        scale: scale.min(precision) as u8,
+                    },
+                }
             }
-            s if s.starts_with("numeric") || s.starts_with("decimal") => ColumnType::Decimal {
-                precision: 38,
-                scale: 9,
-            },
             _ => ColumnType::Text, // Default fallback
         }
     }
+
+    /// Parse a DuckDB type name such as `DECIMAL(18,4)`, `JSON` or `BIGINT[]`.
+    ///
+    /// Accepts the types this crate creates plus their common aliases (`INT`,
+    /// `FLOAT8`, `TEXT`, `TIMESTAMP WITH TIME ZONE`, ...). Returns `None` for
+    /// anything else, including `DECIMAL` wider than 38 digits.
+    pub fn from_duckdb(type_name: &str) -> Option<Self> {
+        let normalized = type_name.trim().to_uppercase();
+        if let Some(element) = normalized.strip_suffix("[]") {
+            return match ColumnType::from_duckdb(element)? {
+                ColumnType::Array(_) => None,
+                element => Some(ColumnType::Array(Box::new(element))),
+            };
+        }
+
+        let base = normalized
+            .split_once('(')
+            .map_or(normalized.as_str(), |(base, _)| base)
+            .trim();
+        let modifiers = type_modifiers(&normalized);
+        if normalized.contains('(') && modifiers.is_empty() {
+            return None;
+        }
+
+        let column_type = match (base, &modifiers[..]) {
+            ("BOOLEAN" | "BOOL" | "LOGICAL", []) => ColumnType::Boolean,
+            ("SMALLINT" | "INT2" | "SHORT", []) => ColumnType::SmallInt,
+            ("INTEGER" | "INT" | "INT4" | "SIGNED", []) => ColumnType::Integer,
+            ("BIGINT" | "INT8" | "LONG", []) => ColumnType::BigInt,
+            ("REAL" | "FLOAT" | "FLOAT4", []) => ColumnType::Real,
+            ("DOUBLE" | "FLOAT8", []) => ColumnType::Double,
+            // DuckDB's own default for a bare DECIMAL
+            ("DECIMAL" | "NUMERIC", []) => ColumnType::Decimal {
+                precision: 18,
+                scale: 3,
+            },
+            ("DECIMAL" | "NUMERIC", [precision]) if (1..=38).contains(precision) => {
+                ColumnType::Decimal {
+                    precision: *precision as u8,
+                    scale: 0,
+                }
+            }
+            ("DECIMAL" | "NUMERIC", [precision, scale])
+                if (1..=38).contains(precision) && scale <= precision =>
+            {
+                ColumnType::Decimal {
+                    precision: *precision as u8,
+                    scale: *scale as u8,
+                }
+            }
+            ("VARCHAR", []) => ColumnType::Varchar { max_length: None },
+            ("VARCHAR", [len]) => ColumnType::Varchar {
+                max_length: Some(*len),
+            },
+            ("TEXT" | "STRING", []) => ColumnType::Text,
+            ("DATE", []) => ColumnType::Date,
+            ("TIME", []) => ColumnType::Time,
+            ("TIMESTAMP", []) => ColumnType::Timestamp,
+            ("TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE", []) => ColumnType::TimestampTz,
+            ("JSON", []) => ColumnType::Json,
+            ("BLOB" | "BYTEA", []) => ColumnType::Blob,
+            _ => return None,
+        };
+        Some(column_type)
+    }
+}
+
+/// Parse the modifiers of a type name like `numeric(10,2)` into `[10, 2]`.
+fn type_modifiers(pg_type: &str) -> Vec<u32> {
+    pg_type
+        .split_once('(')
+        .and_then(|(_, rest)| rest.strip_suffix(')'))
+        .map(|args| {
+            args.split(',')
+                .filter_map(|a| a.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 impl fmt::Display for ColumnType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_duckdb())
+        write!(f, "{}", self.to_duckdb_ddl_fragment())
     }
 }
 
@@ -311,12 +456,13 @@ impl Index {
     /// Generate CREATE INDEX DDL.
     pub fn to_ddl(&self, table_name: &str) -> String {
         let unique = if self.unique { "UNIQUE " } else { "" };
+        let cols: Vec<String> = self.columns.iter().map(|c| quote_identifier(c)).collect();
         format!(
             "CREATE {}INDEX IF NOT EXISTS {} ON {} ({})",
             unique,
-            self.name,
-            table_name,
-            self.columns.join(", ")
+            quote_identifier(&self.name),
+            quote_qualified_identifier(table_name),
+            cols.join(", ")
         )
     }
 }
@@ -347,6 +493,15 @@ impl IntrospectedColumn {
             unique: false,
         }
     }
+
+    /// DuckDB type for this column: its entry in `type_overrides` if there is
+    /// one, otherwise the type inferred from `pg_type`.
+    pub fn column_type(&self, type_overrides: &HashMap<String, String>) -> ColumnType {
+        type_overrides
+            .get(&self.name)
+            .and_then(|t| ColumnType::from_duckdb(t))
+            .unwrap_or_else(|| ColumnType::from_postgres(&self.pg_type))
+    }
 }
 
 #[cfg(test)]
@@ -364,12 +519,31 @@ mod tests {
             },
         ));
         table.add_column(Column::new("created_at", ColumnType::TimestampTz));
+        table.add_column(Column::new(
+            "price",
+            ColumnType::Decimal {
+                precision: 10,
+                scale: 2,
+            },
+        ));
         table.set_primary_key(vec!["id".to_string()]);
 
         let ddl = table.to_duckdb_ddl();
-        assert!(ddl.contains("CREATE TABLE IF NOT EXISTS test_table"));
-        assert!(ddl.contains("id INTEGER NOT NULL"));
-        assert!(ddl.contains("PRIMARY KEY (id)"));
+        assert!(ddl.contains(r#"CREATE TABLE IF NOT EXISTS "test_table""#));
+        assert!(ddl.contains(r#""id" INTEGER NOT NULL"#));
+        assert!(ddl.contains(r#""name" VARCHAR(255)"#));
+        assert!(ddl.contains(r#""price" DECIMAL(10, 2)"#));
+        assert!(ddl.contains(r#"PRIMARY KEY ("id")"#));
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("my table"), r#""my table""#);
+        assert_eq!(quote_identifier(r#"we"ird"#), r#""we""ird""#);
+        assert_eq!(
+            quote_qualified_identifier("public.Users"),
+            r#""public"."Users""#
+        );
     }
 
     #[test]
@@ -380,5 +554,97 @@ mod tests {
             ColumnType::TimestampTz
         );
         assert_eq!(ColumnType::from_postgres("jsonb"), ColumnType::Json);
+        assert_eq!(
+            ColumnType::from_postgres("numeric(10,2)"),
+            ColumnType::Decimal {
+                precision: 10,
+                scale: 2
+            }
+        );
+        assert_eq!(
+            ColumnType::from_postgres("numeric"),
+            ColumnType::Decimal {
+                precision: 38,
+                scale: 9
+            }
+        );
+        assert_eq!(
+            ColumnType::from_postgres("numeric(50,4)"),
+            ColumnType::Double
+        );
+        assert_eq!(
+            ColumnType::from_postgres("character varying(255)"),
+            ColumnType::Varchar {
+                max_length: Some(255)
+            }
+        );
+    }
+
+    #[test]
+    fn test_column_type_from_duckdb() {
+        assert_eq!(
+            ColumnType::from_duckdb("DECIMAL(18,4)"),
+            Some(ColumnType::Decimal {
+                precision: 18,
+                scale: 4
+            })
+        );
+        assert_eq!(ColumnType::from_duckdb("json"), Some(ColumnType::Json));
+        assert_eq!(
+            ColumnType::from_duckdb("timestamp with time zone"),
+            Some(ColumnType::TimestampTz)
+        );
+        assert_eq!(
+            ColumnType::from_duckdb("BIGINT[]"),
+            Some(ColumnType::Array(Box::new(ColumnType::BigInt)))
+        );
+        assert_eq!(
+            ColumnType::from_duckdb("varchar(64)").map(|t| t.to_duckdb_ddl_fragment()),
+            Some("VARCHAR(64)".to_string())
+        );
+
+        assert_eq!(ColumnType::from_duckdb("DECIMAL(40,2)"), None);
+        assert_eq!(ColumnType::from_duckdb("DECIMAL(4,6)"), None);
+        assert_eq!(ColumnType::from_duckdb("INTEGER(10)"), None);
+        assert_eq!(ColumnType::from_duckdb("HUGEINT"), None);
+        assert_eq!(ColumnType::from_duckdb("INT[][]"), None);
+    }
+
+    #[test]
+    fn test_column_type_override() {
+        let amount = IntrospectedColumn {
+            name: "amount".to_string(),
+            pg_type: "numeric".to_string(),
+            nullable: true,
+            default: None,
+            is_primary_key: false,
+        };
+        let overrides = HashMap::from([("amount".to_string(), "DECIMAL(18,4)".to_string())]);
+
+        assert_eq!(
+            amount.column_type(&overrides).to_duckdb_ddl_fragment(),
+            "DECIMAL(18, 4)"
+        );
+        assert_eq!(
+            amount.column_type(&HashMap::new()).to_duckdb_ddl_fragment(),
+            "DECIMAL(38, 9)"
+        );
+    }
+
+    #[test]
+    fn test_array_types() {
+        let int_array = ColumnType::Array(Box::new(ColumnType::Integer));
+        assert_eq!(ColumnType::from_postgres("_int4"), int_array);
+        assert_eq!(ColumnType::from_postgres("integer[]"), int_array);
+        assert_eq!(int_array.to_duckdb_ddl_fragment(), "INTEGER[]");
+        assert_eq!(
+            ColumnType::from_postgres("_text").to_duckdb_ddl_fragment(),
+            "VARCHAR[]"
+        );
+        assert_eq!(
+            ColumnType::from_postgres("_numeric").to_duckdb_ddl_fragment(),
+            "DECIMAL(38, 9)[]"
+        );
+        assert_eq!(ColumnType::from_postgres("integer[][]"), ColumnType::Text);
     }
 }
@@ -0,0 +1,412 @@
+//! Destinations a sync writes to.
+//!
+//! [`SyncClient`](crate::SyncClient) drives its target through the [`SyncSink`]
+//! trait. [`MotherDuckClient`](crate::MotherDuckClient) is the built-in sink and
+//! also covers local DuckDB files (`motherduck.local_path`); other warehouses
+//! plug in with [`SyncClient::new_with_sink`](crate::SyncClient::new_with_sink).
+
+use crate::config::{AnalyticsTablesConfig, TableMapping, TransactionScope};
+use crate::error::{Error, Result};
+use crate::motherduck::{SchemaMismatch, StatementWatchdog};
+use crate::schema::{Index, IntrospectedColumn};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+
+/// A destination for synced rows.
+///
+/// Methods are synchronous; the sync runs them off the async runtime's hot
+/// path. Only the required methods are needed for incremental and full syncs.
+/// The provided ones default to skipping optional work (transactions, schema
+/// migration, analytics tables) or, for features a sink can't offer (atomic
+/// swaps, bulk loads, `post_sql`, raw SQL), to failing with a config error.
+pub trait SyncSink: Send {
+    /// Check that the destination answers.
+    fn ping(&self) -> Result<()>;
+
+    /// Reopen the connection after [`ping`](Self::ping) failed, keeping the
+    /// existing one on error. The default does nothing.
+    fn reconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Arm a watchdog interrupting the next statement if it overruns, or
+    /// `None` if the sink has no statement timeout.
+    fn statement_watchdog(&self) -> Option<StatementWatchdog> {
+        None
+    }
+
+    /// Whether `table` exists.
+    fn table_exists(&self, table: &str) -> Result<bool>;
+
+    /// Create `table` with `columns` unless it already exists.
+    fn ensure_table(
+        &self,
+        table: &str,
+        columns: &[IntrospectedColumn],
+        primary_key: &[String],
+        indexes: &[Index],
+        type_overrides: &HashMap<String, String>,
+    ) -> Result<()>;
+
+    /// Add any of `columns` missing from `table`, returning their names.
+    fn reconcile_schema(
+        &self,
+        _table: &str,
+        _columns: &[IntrospectedColumn],
+        _type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Columns of `table` whose type no longer matches their source column.
+    fn check_schema_compatibility(
+        &self,
+        _table: &str,
+        _columns: &[IntrospectedColumn],
+        _type_overrides: &HashMap<String, String>,
+    ) -> Result<Vec<SchemaMismatch>> {
+        Ok(Vec::new())
+    }
+
+    /// Insert or update `rows` in the mapping's target table, returning the
+    /// number written.
+    fn upsert(&self, mapping: &TableMapping, rows: &[HashMap<String, JsonValue>]) -> Result<usize>;
+
+    /// [`upsert`](Self::upsert) `rows` in chunks of `batch_size`, reporting the
+    /// running total to `progress`.
+    ///
+    /// With [`TransactionScope::PerTable`] the whole call is one transaction
+    /// and runs the mapping's `post_sql` before committing. The default wraps
+    /// each chunk, or the whole call, in [`begin`](Self::begin) and
+    /// [`commit`](Self::commit) accordingly.
+    fn batch_upsert(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+        batch_size: usize,
+        scope: TransactionScope,
+        progress: &mut dyn FnMut(usize),
+    ) -> Result<usize> {
+        if rows.is_empty() {
+            return Ok(0);
+        }
+        let table = &mapping.target_table;
+        let in_transaction = |f: &mut dyn FnMut() -> Result<usize>| {
+            self.begin(table)?;
+            match f() {
+                Ok(written) => self.commit(table).map(|_| written),
+                Err(e) => {
+                    self.rollback();
+                    Err(e)
+                }
+            }
+        };
+        match scope {
+            TransactionScope::PerBatch => {
+                let mut written = 0;
+                for chunk in rows.chunks(batch_size.max(1)) {
+                    written += in_transaction(&mut || self.upsert(mapping, chunk))?;
+                    progress(written);
+                }
+                Ok(written)
+            }
+            TransactionScope::PerTable => in_transaction(&mut || {
+                let mut written = 0;
+                for chunk in rows.chunks(batch_size.max(1)) {
+                    written += self.upsert(mapping, chunk)?;
+                    progress(written);
+                }
+                self.run_post_sql(mapping)?;
+                Ok(written)
+            }),
+        }
+    }
+
+    /// Insert `rows` into a freshly created staging table. Defaults to
+    /// [`upsert`](Self::upsert).
+    fn append_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, JsonValue>],
+    ) -> Result<usize> {
+        self.upsert(mapping, rows)
+    }
+
+    /// Load a CSV file with `columns` into the mapping's target table.
+    fn load_csv(&self, _mapping: &TableMapping, _columns: &[String], _path: &str) -> Result<usize> {
+        Err(unsupported("bulk_copy"))
+    }
+
+    /// Delete the rows with the given primary keys.
+    fn delete_rows(&self, mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<usize>;
+
+    /// Delete the rows whose primary key is not in `live_keys`.
+    fn delete_missing(&self, mapping: &TableMapping, live_keys: &[Vec<JsonValue>])
+    -> Result<usize>;
+
+    /// Number of rows in `table`.
+    fn count_rows(&self, table: &str) -> Result<i64>;
+
+    /// Drop `table` if it exists.
+    fn drop_table(&self, table: &str) -> Result<()>;
+
+    /// Replace `target` with `staging` in one step, recreating `indexes`.
+    fn swap_table(&self, _staging: &str, _target: &str, _indexes: &[Index]) -> Result<()> {
+        Err(unsupported("atomic_swap"))
+    }
+
+    /// Start a transaction around one table's writes. The default does nothing.
+    fn begin(&self, _table: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Commit the transaction opened by [`begin`](Self::begin).
+    fn commit(&self, _table: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Roll back the transaction opened by [`begin`](Self::begin).
+    fn rollback(&self) {}
+
+    /// Create the configured schema. The default does nothing.
+    fn ensure_schema(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Create the aggregated analytics tables. The default does nothing.
+    fn create_analytics_tables(&self, _tables: &AnalyticsTablesConfig) -> Result<()> {
+        Ok(())
+    }
+
+    /// Create the table [`record_sync`](Self::record_sync) and the watermark
+    /// methods write to. The default does nothing.
+    fn ensure_sync_metadata(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// The watermark stored by the last sync of `table`.
+    fn get_watermark(&self, table: &str) -> Result<Option<JsonValue>>;
+
+    /// Store the watermark reached by a sync of `table`.
+    fn set_watermark(
+        &self,
+        table: &str,
+        watermark: &JsonValue,
+        records_synced: usize,
+        sync_mode: &str,
+    ) -> Result<()>;
+
+    /// Record that `table` finished syncing. The default does nothing.
+    fn record_sync(&self, _table: &str, _records_synced: usize, _sync_mode: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Refresh `table`'s statistics after a sync. The default does nothing.
+    fn analyze_table(&self, _table: &str) -> Result<()> {
+        Ok(())
+    }
+
+    /// Flush written data to storage. The default does nothing.
+    fn checkpoint(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Run the mapping's `post_sql`, if any.
+    fn run_post_sql(&self, mapping: &TableMapping) -> Result<()> {
+        match mapping.post_sql {
+            Some(_) => Err(unsupported("post_sql")),
+            None => Ok(()),
+        }
+    }
+
+    /// The statements a sync of `mapping` would run, for `--show-sql`.
+    fn preview_writes(
+        &self,
+        _mapping: &TableMapping,
+        _columns: &[IntrospectedColumn],
+        _indexes: &[Index],
+        _create: bool,
+    ) -> Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
+    /// Execute a SQL statement, returning the rows changed. For table hooks.
+    fn execute(&self, _sql: &str) -> Result<usize> {
+        Err(unsupported("execute"))
+    }
+}
+
+fn unsupported(feature: &str) -> Error {
+    Error::config(format!("{} is not supported by this sink", feature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the calls the provided methods make.
+    #[derive(Default)]
+    struct RecordingSink {
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl RecordingSink {
+        fn record(&self, call: impl Into<String>) {
+            self.calls.lock().unwrap().push(call.into());
+        }
+    }
+
+    impl SyncSink for RecordingSink {
+        fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+
+        fn table_exists(&self, _table: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn ensure_table(
+            &self,
+            _table: &str,
+            _columns: &[IntrospectedColumn],
+            _primary_key: &[String],
+            _indexes: &[Index],
+            _type_overrides: &HashMap<String, String>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn upsert(
+            &self,
+            _mapping: &TableMapping,
+            rows: &[HashMap<String, JsonValue>],
+        ) -> Result<usize> {
+            self.record(format!("upsert {}", rows.len()));
+            Ok(rows.len())
+        }
+
+        fn delete_rows(&self, _mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<usize> {
+            Ok(keys.len())
+        }
+
+        fn delete_missing(
+            &self,
+            _mapping: &TableMapping,
+            _live_keys: &[Vec<JsonValue>],
+        ) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn count_rows(&self, _table: &str) -> Result<i64> {
+            Ok(0)
+        }
+
+        fn drop_table(&self, _table: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn begin(&self, _table: &str) -> Result<()> {
+            self.record("begin");
+            Ok(())
+        }
+
+        fn commit(&self, _table: &str) -> Result<()> {
+            self.record("commit");
+            Ok(())
+        }
+
+        fn get_watermark(&self, _table: &str) -> Result<Option<JsonValue>> {
+            Ok(None)
+        }
+
+        fn set_watermark(
+            &self,
+            _table: &str,
+            _watermark: &JsonValue,
+            _records_synced: usize,
+            _sync_mode: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn rows(n: usize) -> Vec<HashMap<String, JsonValue>> {
+        (0..n)
+            .map(|i| HashMap::from([("id".to_string(), JsonValue::from(i))]))
+            .collect()
+    }
+
+    #[test]
+    fn test_default_batch_upsert() {
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key(["id"])
+            .build()
+            .unwrap();
+
+        let sink = RecordingSink::default();
+        let mut progress = Vec::new();
+        let written = sink
+            .batch_upsert(
+                &mapping,
+                &rows(5),
+                2,
+                TransactionScope::PerBatch,
+                &mut |n| progress.push(n),
+            )
+            .unwrap();
+        assert_eq!(written, 5);
+        assert_eq!(progress, vec![2, 4, 5]);
+        assert_eq!(
+            *sink.calls.lock().unwrap(),
+            [
+                "begin", "upsert 2", "commit", "begin", "upsert 2", "commit", "begin", "upsert 1",
+                "commit"
+            ]
+        );
+
+        let sink = RecordingSink::default();
+        sink.batch_upsert(
+            &mapping,
+            &rows(3),
+            2,
+            TransactionScope::PerTable,
+            &mut |_| {},
+        )
+        .unwrap();
+        assert_eq!(
+            *sink.calls.lock().unwrap(),
+            ["begin", "upsert 2", "upsert 1", "commit"]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_defaults() {
+        let sink = RecordingSink::default();
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key(["id"])
+            .post_sql("UPDATE {{target}} SET n = 1")
+            .build()
+            .unwrap();
+
+        let err = sink
+            .batch_upsert(
+                &mapping,
+                &rows(1),
+                10,
+                TransactionScope::PerTable,
+                &mut |_| {},
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("post_sql is not supported"));
+        assert_eq!(
+            *sink.calls.lock().unwrap(),
+            ["begin", "upsert 1"],
+            "a failed table transaction is not committed"
+        );
+        assert!(sink.swap_table("a", "b", &[]).is_err());
+        assert!(sink.execute("SELECT 1").is_err());
+    }
+}
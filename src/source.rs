@@ -0,0 +1,170 @@
+//! Databases a sync reads from.
+//!
+//! [`SyncClient`](crate::SyncClient) reads its source through the
+//! [`SyncSource`] trait. [`PostgresClient`](crate::postgres::PostgresClient) is
+//! the built-in source; others plug in with
+//! [`SyncClient::with_backends`](crate::SyncClient::with_backends).
+
+use crate::config::TableMapping;
+use crate::error::{Error, Result};
+use crate::schema::{Index, IntrospectedColumn};
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// A source row, keyed by column name.
+pub type SourceRow = HashMap<String, JsonValue>;
+
+/// A source of rows to sync.
+///
+/// Only the required methods are needed for incremental and full syncs. The
+/// provided ones cover optional features (streaming, keyset pagination, bulk
+/// copy, `delete_missing`) and default to failing with a config error, or for
+/// secondary indexes and reconnecting, to doing nothing.
+#[async_trait]
+pub trait SyncSource: Send + Sync {
+    /// Check that the source answers.
+    async fn ping(&self) -> Result<()>;
+
+    /// Replace the source's connections with fresh ones, keeping the existing
+    /// ones on error. The default does nothing.
+    async fn reconnect(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Whether `table` exists.
+    async fn table_exists(&self, table: &str) -> Result<bool>;
+
+    /// The columns of `table`, in ordinal order.
+    async fn introspect_table(&self, table: &str) -> Result<Vec<IntrospectedColumn>>;
+
+    /// The columns a mapping reads. The default introspects its table and
+    /// rejects `source_query` mappings.
+    async fn introspect_source(&self, mapping: &TableMapping) -> Result<Vec<IntrospectedColumn>> {
+        match mapping.source_query {
+            Some(_) => Err(unsupported("query")),
+            None => self.introspect_table(&mapping.source_table).await,
+        }
+    }
+
+    /// Secondary indexes of `table` to recreate on the target. The default
+    /// reports none.
+    async fn introspect_indexes(&self, _table: &str) -> Result<Vec<Index>> {
+        Ok(Vec::new())
+    }
+
+    /// Rows to sync: every row for a full sync, else the unsynced rows or those
+    /// past the `since` watermark. At most `limit` rows when set.
+    async fn fetch_rows(
+        &self,
+        mapping: &TableMapping,
+        full_sync: bool,
+        limit: Option<usize>,
+        since: Option<&JsonValue>,
+    ) -> Result<Vec<SourceRow>>;
+
+    /// The query [`fetch_rows`](Self::fetch_rows) runs, for `--show-sql`;
+    /// `has_since` says whether a stored watermark is bound.
+    fn fetch_statement(
+        &self,
+        _mapping: &TableMapping,
+        _full_sync: bool,
+        _has_since: bool,
+    ) -> Option<String> {
+        None
+    }
+
+    /// Up to `limit` rows ordered by primary key, starting after the key
+    /// object `after` (for `pagination = "keyset"`).
+    async fn fetch_page(
+        &self,
+        _mapping: &TableMapping,
+        _full_sync: bool,
+        _limit: usize,
+        _since: Option<&JsonValue>,
+        _after: Option<&JsonValue>,
+    ) -> Result<Vec<SourceRow>> {
+        Err(unsupported("keyset pagination"))
+    }
+
+    /// Open a cursor over the rows [`fetch_rows`](Self::fetch_rows) would
+    /// return (for `sync.streaming`).
+    async fn open_cursor<'a>(
+        &'a self,
+        _mapping: &'a TableMapping,
+        _full_sync: bool,
+        _limit: Option<usize>,
+        _since: Option<&JsonValue>,
+    ) -> Result<Box<dyn SourceCursor + 'a>> {
+        Err(unsupported("streaming"))
+    }
+
+    /// Write `columns` of every row to a CSV file at `path`, returning its
+    /// size in bytes (for `sync.bulk_copy`).
+    async fn copy_to_csv(
+        &self,
+        _mapping: &TableMapping,
+        _columns: &[String],
+        _limit: Option<usize>,
+        _path: &Path,
+    ) -> Result<u64> {
+        Err(unsupported("bulk_copy"))
+    }
+
+    /// Primary keys of every row, for `delete_missing`.
+    async fn fetch_primary_keys(&self, _mapping: &TableMapping) -> Result<Vec<Vec<JsonValue>>> {
+        Err(unsupported("delete_missing"))
+    }
+
+    /// Mark the rows with the given primary keys as synced.
+    async fn mark_synced(&self, mapping: &TableMapping, keys: &[Vec<JsonValue>]) -> Result<u64>;
+
+    /// The statement [`mark_synced`](Self::mark_synced) runs, for `--show-sql`.
+    fn mark_synced_statement(&self, _mapping: &TableMapping) -> Option<String> {
+        None
+    }
+
+    /// Number of rows an incremental sync of `mapping` would fetch.
+    async fn unsynced_count(
+        &self,
+        mapping: &TableMapping,
+        since: Option<&JsonValue>,
+    ) -> Result<i64>;
+
+    /// Number of rows in `table` matching `filter`.
+    async fn count_rows(&self, table: &str, filter: Option<&str>) -> Result<i64>;
+}
+
+/// Rows read in batches from an open [`SyncSource::open_cursor`].
+///
+/// Call [`close`](Self::close) once done, or [`abort`](Self::abort) on failure.
+#[async_trait]
+pub trait SourceCursor: Send + Sync {
+    /// Fetch up to `size` more rows. Returns an empty batch once exhausted.
+    async fn next_batch(&mut self, size: usize) -> Result<Vec<SourceRow>>;
+
+    /// Like [`next_batch`](Self::next_batch), but may end the batch early once
+    /// its rows pass `max_bytes`. The default ignores the bound.
+    async fn next_batch_bounded(
+        &mut self,
+        size: usize,
+        _max_bytes: Option<usize>,
+    ) -> Result<Vec<SourceRow>> {
+        self.next_batch(size).await
+    }
+
+    /// Mark rows read from this cursor as synced, taking effect on
+    /// [`close`](Self::close).
+    async fn mark_synced(&self, keys: &[Vec<JsonValue>]) -> Result<u64>;
+
+    /// Finish reading, keeping the marks.
+    async fn close(self: Box<Self>) -> Result<()>;
+
+    /// Stop reading, discarding the marks.
+    async fn abort(self: Box<Self>);
+}
+
+fn unsupported(feature: &str) -> Error {
+    Error::config(format!("{} is not supported by this source", feature))
+}
@@ -1,16 +1,43 @@
 //! Core sync logic for motherduck-supasync.
 
-use crate::config::{SyncConfig, TableMapping};
-use crate::error::Result;
+use crate::checkpoint::Checkpoint;
+use crate::config::{
+    FailurePolicy, FullRefreshStrategy, OversizedFieldPolicy, Pagination, RetryConfig,
+    SyncBehaviorConfig, SyncConfig, TableMapping, TransactionScope,
+};
+use crate::error::{Error, Result};
+use crate::metrics::{Metrics, MetricsSnapshot};
 use crate::motherduck::MotherDuckClient;
 use crate::postgres::PostgresClient;
+use crate::schema::{ColumnType, Index, IntrospectedColumn};
+use crate::sink::SyncSink;
+use crate::source::{SourceCursor, SyncSource};
+use backoff::backoff::Backoff;
 use backoff::{ExponentialBackoff, ExponentialBackoffBuilder};
+use futures_util::{StreamExt, TryStreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
+use tokio::runtime::RuntimeFlavor;
+use tokio_util::sync::CancellationToken;
 
-use tracing::{debug, error, info, instrument, warn};
+use tracing::{Instrument, debug, error, info, info_span, instrument, trace, warn};
+
+/// Target column holding the id of the run that last wrote a row, with
+/// `sync.stamp_columns`.
+pub const RUN_ID_COLUMN: &str = "_synced_run_id";
+
+/// Target column holding when the run that last wrote a row started, with
+/// `sync.stamp_columns`.
+pub const SYNCED_AT_COLUMN: &str = "_synced_at";
+
+/// Target column holding a hash of the row's content, for tables with
+/// `row_hash`.
+pub const ROW_HASH_COLUMN: &str = "_row_hash";
 
 /// Sync mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -20,6 +47,8 @@ pub enum SyncMode {
     Incremental,
     /// Sync all records
     Full,
+    /// Fetch unsynced records and report counts without writing anything
+    DryRun,
 }
 
 impl std::fmt::Display for SyncMode {
@@ -27,6 +56,7 @@ impl std::fmt::Display for SyncMode {
         match self {
             SyncMode::Incremental => write!(f, "incremental"),
             SyncMode::Full => write!(f, "full"),
+            SyncMode::DryRun => write!(f, "dry-run"),
         }
     }
 }
@@ -63,6 +93,127 @@ impl SyncResult {
     pub fn all_tables_success(&self) -> bool {
         self.tables.values().all(|t| t.success)
     }
+
+    /// The table that took longest to sync, if any table ran.
+    pub fn slowest_table(&self) -> Option<&TableSyncResult> {
+        self.tables.values().max_by_key(|t| t.duration_ms)
+    }
+
+    /// Tables that failed, sorted by source table name.
+    pub fn failed_tables(&self) -> Vec<&TableSyncResult> {
+        let mut failed: Vec<_> = self.tables.values().filter(|t| !t.success).collect();
+        failed.sort_by(|a, b| a.source_table.cmp(&b.source_table));
+        failed
+    }
+
+    /// Records synced per second over the whole run (0 for an instant run).
+    pub fn throughput_records_per_sec(&self) -> f64 {
+        if self.duration_ms == 0 {
+            return 0.0;
+        }
+        self.total_records() as f64 * 1000.0 / self.duration_ms as f64
+    }
+
+    /// One CSV line per table, sorted by source table name, after a header line.
+    ///
+    /// Every line carries the run's `completed_at` and `mode`, so the lines of
+    /// successive runs can be appended to one file (skipping the header).
+    pub fn to_csv_summary(&self) -> String {
+        let mut csv = String::from(
+            "completed_at,mode,source_table,target_table,success,records_synced,records_failed,duration_ms,error\n",
+        );
+        let mut tables: Vec<_> = self.tables.values().collect();
+        tables.sort_by(|a, b| a.source_table.cmp(&b.source_table));
+        for t in tables {
+            let fields = [
+                csv_field(&self.completed_at),
+                csv_field(&self.mode),
+                csv_field(&t.source_table),
+                csv_field(&t.target_table),
+                t.success.to_string(),
+                t.records_synced.to_string(),
+                t.records_failed.to_string(),
+                t.duration_ms.to_string(),
+                csv_field(t.error.as_deref().unwrap_or_default()),
+            ];
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Compare this run with an earlier one.
+    ///
+    /// Covers every table in either run; a table missing from one run counts
+    /// as having synced nothing in no time there.
+    pub fn delta(&self, previous: &SyncResult) -> SyncDelta {
+        let mut delta = SyncDelta {
+            records_synced: self.total_records() as i64 - previous.total_records() as i64,
+            duration_ms: self.duration_ms as i64 - previous.duration_ms as i64,
+            ..Default::default()
+        };
+
+        let names: BTreeSet<&String> = self.tables.keys().chain(previous.tables.keys()).collect();
+        for name in names {
+            let current = self.tables.get(name);
+            let before = previous.tables.get(name);
+            let records = |t: Option<&TableSyncResult>| t.map_or(0, |t| t.records_synced as i64);
+            let duration = |t: Option<&TableSyncResult>| t.map_or(0, |t| t.duration_ms as i64);
+            delta.tables.insert(
+                name.clone(),
+                TableDelta {
+                    records_synced: records(current) - records(before),
+                    duration_ms: duration(current) - duration(before),
+                    previous_duration_ms: before.map(|t| t.duration_ms),
+                },
+            );
+
+            let failed_now = current.is_some_and(|t| !t.success);
+            let failed_before = before.is_some_and(|t| !t.success);
+            if failed_now && !failed_before {
+                delta.newly_failing.push(name.clone());
+            } else if failed_before && current.is_some_and(|t| t.success) {
+                delta.recovered.push(name.clone());
+            }
+        }
+
+        delta
+    }
+}
+
+/// How a run differs from an earlier one, as returned by [`SyncResult::delta`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncDelta {
+    /// Change in total records synced
+    pub records_synced: i64,
+    /// Change in total duration in milliseconds
+    pub duration_ms: i64,
+    /// Per-table changes, keyed by source table name
+    pub tables: BTreeMap<String, TableDelta>,
+    /// Tables that failed in this run but not in the earlier one, sorted
+    pub newly_failing: Vec<String>,
+    /// Tables that failed in the earlier run and succeeded in this one, sorted
+    pub recovered: Vec<String>,
+}
+
+/// How one table's sync differs from an earlier run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableDelta {
+    /// Change in records synced
+    pub records_synced: i64,
+    /// Change in duration in milliseconds
+    pub duration_ms: i64,
+    /// Duration in the earlier run, or `None` if the table didn't run then
+    pub previous_duration_ms: Option<u64>,
+}
+
+/// Quote a CSV field if it holds a comma, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
 
 /// Per-table sync result.
@@ -78,15 +229,106 @@ pub struct TableSyncResult {
     pub records_synced: usize,
     /// Records failed
     pub records_failed: usize,
+    /// Records whose row hash matched the target, so their upsert wrote nothing
+    #[serde(default)]
+    pub records_unchanged: usize,
     /// Duration in milliseconds
     pub duration_ms: u64,
     /// Error message if failed
     pub error: Option<String>,
+    /// [`Error::code`] of the failure's root cause, e.g. `PG_CONNECTION_ERROR`
+    #[serde(default)]
+    pub error_code: Option<String>,
+    /// Whether the failure's root cause is transient, so a later run may succeed
+    #[serde(default)]
+    pub retryable: bool,
+    /// Whether this was a dry run (records_synced is the would-be count)
+    #[serde(default)]
+    pub dry_run: bool,
+    /// SQL the sync runs for this table, with values as placeholders
+    /// (see [`SyncClient::with_sql_capture`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sql: Vec<String>,
+    /// Tries the table's operations took: 1 plus every retry of a fetch, upsert
+    /// or delete, so a value above 1 marks a table that only got through flakily
+    #[serde(default)]
+    pub attempts: u32,
+    /// Error behind the table's most recent retry
+    #[serde(default)]
+    pub last_retry_reason: Option<String>,
+}
+
+/// Health of one backend, as checked by [`SyncClient::health`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackendHealth {
+    /// Whether the backend answered
+    pub ok: bool,
+    /// How long the check took in milliseconds
+    pub latency_ms: u64,
+    /// Error message if the check failed
+    pub error: Option<String>,
+}
+
+impl BackendHealth {
+    fn from_check(result: Result<()>, started: Instant) -> Self {
+        Self {
+            ok: result.is_ok(),
+            latency_ms: started.elapsed().as_millis() as u64,
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for BackendHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.error {
+            None => write!(f, "ok ({}ms)", self.latency_ms),
+            Some(ref error) => write!(f, "failed ({}ms): {}", self.latency_ms, error),
+        }
+    }
+}
+
+/// Readiness of both backends, as returned by [`SyncClient::health`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether both backends are healthy
+    pub ok: bool,
+    /// PostgreSQL source
+    pub postgres: BackendHealth,
+    /// MotherDuck target
+    pub motherduck: BackendHealth,
+}
+
+impl HealthReport {
+    fn new(postgres: BackendHealth, motherduck: BackendHealth) -> Self {
+        Self {
+            ok: postgres.ok && motherduck.ok,
+            postgres,
+            motherduck,
+        }
+    }
+}
+
+impl std::fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "PostgreSQL {}, MotherDuck {}",
+            self.postgres, self.motherduck
+        )
+    }
 }
 
 /// Sync progress callback.
 pub type ProgressCallback = Box<dyn Fn(SyncProgress) + Send + Sync>;
 
+/// Hook run before a table is fetched.
+pub type PreTableHook = Box<dyn Fn(&TableMapping, &dyn SyncSink) -> Result<()> + Send + Sync>;
+
+/// Hook run after a table finishes, successfully or not.
+pub type TableHook =
+    Box<dyn Fn(&TableMapping, &TableSyncResult, &dyn SyncSink) -> Result<()> + Send + Sync>;
+
 /// Sync progress update.
 #[derive(Debug, Clone)]
 pub struct SyncProgress {
@@ -100,6 +342,10 @@ pub struct SyncProgress {
     pub total_records: Option<usize>,
     /// Percentage complete (0-100)
     pub percent: u8,
+    /// Observed write throughput for the current table, once rows are being written
+    pub rows_per_second: Option<f64>,
+    /// Estimated time until the current phase finishes, when the total is known
+    pub eta: Option<Duration>,
 }
 
 /// Sync phase.
@@ -135,26 +381,154 @@ impl std::fmt::Display for SyncPhase {
 /// Main sync client.
 pub struct SyncClient {
     config: SyncConfig,
-    pg_client: PostgresClient,
-    md_client: MotherDuckClient,
+    source: Box<dyn SyncSource>,
+    // Behind a lock so a dropped connection can be replaced from `&self`
+    sink: Mutex<Box<dyn SyncSink>>,
     progress_callback: Option<Arc<ProgressCallback>>,
+    pre_table_hooks: Vec<PreTableHook>,
+    table_hooks: Vec<TableHook>,
+    metrics: Arc<Metrics>,
+    capture_sql: bool,
+    // Run id and start time written by `sync.stamp_columns`, set per sync
+    run_stamp: Mutex<(String, String)>,
+    // Retries and the last retry's error for the table being synced
+    table_retries: Mutex<(u32, Option<String>)>,
+    // Values over `sync.max_field_bytes` in the table being synced
+    oversized_values: Mutex<OversizedValues>,
+    // Rows of the table being synced that upserts left alone (see `row_hash`)
+    unchanged_rows: Mutex<usize>,
 }
 
 impl SyncClient {
     /// Create a new sync client.
-    #[instrument(skip(config))]
     pub async fn new(config: SyncConfig) -> Result<Self> {
+        Self::new_with_metrics(config, Arc::new(Metrics::new())).await
+    }
+
+    /// Create a new sync client that records into shared `metrics`.
+    ///
+    /// Lets metrics outlive the client, e.g. when a long-running process
+    /// reconnects or serves them over HTTP.
+    #[instrument(skip(config, metrics))]
+    pub async fn new_with_metrics(config: SyncConfig, metrics: Arc<Metrics>) -> Result<Self> {
+        info!("Initializing sync client...");
+
+        let pg_client = connect_postgres(&config, &metrics).await?;
+        let md_client = connect_motherduck(&config, &metrics).await?;
+
+        Ok(Self::from_parts(
+            config,
+            Box::new(pg_client),
+            Box::new(md_client),
+            metrics,
+        ))
+    }
+
+    /// Create a sync client that writes to `sink` instead of MotherDuck.
+    ///
+    /// The `motherduck` section of `config` is not used for connecting. Sync
+    /// features the sink doesn't support fail the tables that use them.
+    #[instrument(skip(config, sink))]
+    pub async fn new_with_sink(config: SyncConfig, sink: Box<dyn SyncSink>) -> Result<Self> {
         info!("Initializing sync client...");
 
-        let pg_client = PostgresClient::connect(config.postgres.clone()).await?;
-        let md_client = MotherDuckClient::connect(config.motherduck.clone())?;
+        let metrics = Arc::new(Metrics::new());
+        let pg_client = connect_postgres(&config, &metrics).await?;
+        Ok(Self::from_parts(config, Box::new(pg_client), sink, metrics))
+    }
+
+    /// Create a sync client from an already connected source and sink.
+    ///
+    /// Nothing is connected here, so this suits tests with in-memory backends
+    /// as well as sources other than PostgreSQL. `config`'s `postgres` and
+    /// `motherduck` sections are not used for connecting.
+    pub fn with_backends(
+        config: SyncConfig,
+        source: Box<dyn SyncSource>,
+        sink: Box<dyn SyncSink>,
+    ) -> Self {
+        Self::from_parts(config, source, sink, Arc::new(Metrics::new()))
+    }
 
-        Ok(Self {
+    fn from_parts(
+        config: SyncConfig,
+        source: Box<dyn SyncSource>,
+        sink: Box<dyn SyncSink>,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self {
             config,
-            pg_client,
-            md_client,
+            source,
+            sink: Mutex::new(sink),
             progress_callback: None,
-        })
+            pre_table_hooks: Vec::new(),
+            table_hooks: Vec::new(),
+            metrics,
+            capture_sql: false,
+            run_stamp: Mutex::new(new_run_stamp()),
+            table_retries: Mutex::new((0, None)),
+            oversized_values: Mutex::default(),
+            unchanged_rows: Mutex::new(0),
+        }
+    }
+
+    /// Replace both database connections with fresh ones.
+    ///
+    /// Connects with the same retries as [`new`](Self::new); hooks, callbacks and
+    /// metrics are kept. On error the existing connections are left in place.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        info!("Reconnecting sync client...");
+        let mut backoff = create_backoff(&self.config.retry);
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.source.reconnect().await {
+                Ok(()) => break,
+                Err(e) => {
+                    back_off(
+                        &self.config.retry,
+                        &mut backoff,
+                        &self.metrics,
+                        "PostgreSQL connect",
+                        attempts,
+                        e,
+                        &mut |_| {},
+                    )
+                    .await?
+                }
+            }
+        }
+        self.reconnect_sink().await
+    }
+
+    /// Reopen the MotherDuck connection if it stopped answering.
+    ///
+    /// Runs before every sync so a long-lived client recovers from a dropped
+    /// connection instead of failing every run after it. PostgreSQL needs no
+    /// check here: the pool discards closed connections and opens new ones.
+    async fn ensure_connected(&self) -> Result<()> {
+        let Err(e) = self.sink(|sink| sink.ping()) else {
+            return Ok(());
+        };
+        warn!("MotherDuck connection lost ({}), reconnecting", e);
+        self.reconnect_sink().await?;
+        info!("Reconnected to MotherDuck");
+        Ok(())
+    }
+
+    /// Reopen the sink's connection, retrying transient failures.
+    async fn reconnect_sink(&self) -> Result<()> {
+        retry_with_backoff(
+            &self.config.retry,
+            &self.metrics,
+            "MotherDuck connect",
+            || async {
+                let mut sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+                blocking(|| sink.reconnect())
+            },
+            |_| {},
+        )
+        .await
     }
 
     /// Set progress callback.
@@ -166,107 +540,470 @@ impl SyncClient {
         self
     }
 
+    /// Add a hook run before each enabled table is fetched.
+    ///
+    /// Hooks run in the order added and get the sink (the MotherDuck client,
+    /// unless built with [`new_with_sink`](Self::new_with_sink)) to issue their
+    /// own queries. A failing hook is logged and does not stop the sync.
+    pub fn with_pre_table_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&TableMapping, &dyn SyncSink) -> Result<()> + Send + Sync + 'static,
+    {
+        self.pre_table_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Add a hook run after each table completes, e.g. to refresh an aggregate
+    /// built from it.
+    ///
+    /// The hook also runs for failed tables and dry runs; check
+    /// [`TableSyncResult::success`] and [`TableSyncResult::dry_run`]. A failing hook
+    /// is logged and does not change the table's result.
+    pub fn with_table_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&TableMapping, &TableSyncResult, &dyn SyncSink) -> Result<()> + Send + Sync + 'static,
+    {
+        self.table_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Collect the SQL each table's sync runs into [`TableSyncResult::sql`].
+    ///
+    /// Statements are built from the schemas before the table is written: the
+    /// PostgreSQL `SELECT` fetching rows, the target's `CREATE TABLE` when it
+    /// would be created, the upsert, the
+    /// soft-delete `DELETE` and the PostgreSQL `UPDATE` marking rows synced.
+    /// Values are left as placeholders. Combined with [`SyncMode::DryRun`] this
+    /// previews a sync without executing any of it. Bulk copies and atomic swaps
+    /// are shown as the row path they replace.
+    pub fn with_sql_capture(mut self) -> Self {
+        self.capture_sql = true;
+        self
+    }
+
+    /// Run `f` against the sink without stalling the async runtime,
+    /// interrupting it after `motherduck.statement_timeout_secs`.
+    fn sink<T>(&self, f: impl FnOnce(&dyn SyncSink) -> T) -> T {
+        let sink = self.sink.lock().unwrap_or_else(|e| e.into_inner());
+        let _watchdog = sink.statement_watchdog();
+        blocking(|| f(sink.as_ref()))
+    }
+
+    /// [`retry_with_backoff`] for a table's operations, counting each retry
+    /// towards the table's [`TableSyncResult::attempts`].
+    async fn retry<T, F, Fut>(&self, operation: &str, op: F) -> Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        retry_with_backoff(&self.config.retry, &self.metrics, operation, op, |e| {
+            let mut retries = self.table_retries.lock().unwrap_or_else(|e| e.into_inner());
+            retries.0 += 1;
+            retries.1 = Some(e.to_string());
+        })
+        .await
+    }
+
+    /// Get a snapshot of the metrics collected by this client.
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
     /// Test connectivity to both databases.
     pub async fn test_connectivity(&self) -> Result<()> {
         info!("Testing connectivity...");
 
-        self.pg_client.ping().await?;
+        self.source.ping().await?;
         info!("PostgreSQL: OK");
 
-        self.md_client.ping()?;
+        self.sink(|sink| sink.ping())?;
         info!("MotherDuck: OK");
 
         Ok(())
     }
 
+    /// Check both backends and report which answered and how quickly.
+    ///
+    /// Unlike [`test_connectivity`](Self::test_connectivity) this never fails:
+    /// both backends are always checked and any error ends up in the report.
+    pub async fn health(&self) -> HealthReport {
+        let started = Instant::now();
+        let postgres = BackendHealth::from_check(self.source.ping().await, started);
+
+        let started = Instant::now();
+        let motherduck = BackendHealth::from_check(self.sink(|sink| sink.ping()), started);
+
+        let report = HealthReport::new(postgres, motherduck);
+        debug!("Health: {}", report);
+        report
+    }
+
     /// Run sync.
-    #[instrument(skip(self), fields(mode = %mode))]
     pub async fn sync(&self, mode: SyncMode) -> Result<SyncResult> {
+        self.sync_with_cancel(mode, CancellationToken::new()).await
+    }
+
+    /// Run sync, stopping early once `cancel` is triggered.
+    ///
+    /// Cancellation is checked between tables, before writing a fetched table and,
+    /// when streaming, between batches. A cancelled sync returns
+    /// [`Error::Cancelled`] holding the results of the tables that finished.
+    /// With `sync.failure_policy = "abort"` the first failed table ends the run
+    /// with [`Error::Aborted`], which holds the results so far.
+    pub async fn sync_with_cancel(
+        &self,
+        mode: SyncMode,
+        cancel: CancellationToken,
+    ) -> Result<SyncResult> {
+        let tables: Vec<&TableMapping> = self.config.tables.iter().collect();
+        self.sync_mappings(mode, &tables, true, cancel).await
+    }
+
+    /// Run sync for the named tables only.
+    ///
+    /// Each name matches a table's source or target name. Unknown or disabled
+    /// tables are an error. Checkpoints are neither read nor written, so a
+    /// targeted re-sync never interferes with an interrupted full run.
+    pub async fn sync_tables(&self, mode: SyncMode, names: &[&str]) -> Result<SyncResult> {
+        let tables = select_tables(&self.config.tables, names)?;
+        self.sync_mappings(mode, &tables, false, CancellationToken::new())
+            .await
+    }
+
+    #[instrument(name = "sync", skip(self, tables, cancel), fields(mode = %mode, tables = tables.len()))]
+    async fn sync_mappings(
+        &self,
+        mode: SyncMode,
+        tables: &[&TableMapping],
+        use_checkpoint: bool,
+        cancel: CancellationToken,
+    ) -> Result<SyncResult> {
         let start = Instant::now();
-        let full_sync = mode == SyncMode::Full;
+        let dry_run = mode == SyncMode::DryRun;
 
         info!("Starting {} sync...", mode);
-        info!("Config has {} tables", self.config.tables.len());
+        info!(
+            "Syncing {} of {} tables",
+            tables.len(),
+            self.config.tables.len()
+        );
+
+        self.ensure_connected().await?;
+
+        // Create aggregated analytics tables (not synced from PostgreSQL) only
+        // when configured, independently of target tables
+        let create_analytics =
+            self.config.sync.create_analytics_tables && !self.config.analytics_tables.is_empty();
+        if (self.config.sync.auto_create_tables || create_analytics) && !dry_run {
+            self.sink(|sink| sink.ensure_schema())?;
+        }
+        if create_analytics && !dry_run {
+            self.sink(|sink| sink.create_analytics_tables(&self.config.analytics_tables))?;
+        }
+
+        if !dry_run {
+            self.sink(|sink| sink.ensure_sync_metadata())?;
+        }
+
+        let mut checkpoint = match self.config.sync.checkpoint_path {
+            Some(ref path) if use_checkpoint && !dry_run => {
+                Some(Checkpoint::resume_or_start(path, &mode.to_string())?)
+            }
+            _ => None,
+        };
 
-        // Ensure MotherDuck schema exists
-        if self.config.sync.auto_create_tables {
-            self.md_client.ensure_schema()?;
-            // Create aggregated analytics tables (not synced from PostgreSQL)
-            self.md_client.create_analytics_tables()?;
+        // A resumed run keeps stamping rows with the checkpoint's run id
+        let mut stamp = new_run_stamp();
+        if let Some(ref checkpoint) = checkpoint {
+            stamp.0 = checkpoint.run_id.clone();
         }
+        if self.config.sync.stamp_columns {
+            debug!("Stamping rows with run id {}", stamp.0);
+        }
+        *self.run_stamp.lock().unwrap_or_else(|e| e.into_inner()) = stamp;
 
         let mut table_results = HashMap::new();
         let mut overall_success = true;
+        let mut cancelled = false;
+        let mut aborted = None;
 
         // Sync each enabled table
-        for mapping in &self.config.tables {
+        for &mapping in tables {
             if !mapping.enabled {
                 debug!("Skipping disabled table: {}", mapping.source_table);
                 continue;
             }
+            if cancel.is_cancelled() {
+                cancelled = true;
+                break;
+            }
+            if let Some(ref checkpoint) = checkpoint {
+                if checkpoint.is_complete(&mapping.source_table) {
+                    info!(
+                        "Skipping {}: already synced in run {}",
+                        mapping.source_table, checkpoint.run_id
+                    );
+                    continue;
+                }
+            }
 
             info!(
                 "Syncing table: {} -> {}",
                 mapping.source_table, mapping.target_table
             );
 
-            // Auto-create target table from source schema if enabled
-            if self.config.sync.auto_create_tables {
+            // A missing source fails the table before any target DDL runs
+            let mut schema_error = self.check_source_exists(mapping).await.err();
+
+            // Captured before the target is created, so the DDL shows up
+            let sql = if self.capture_sql && schema_error.is_none() {
+                self.preview_sql(mapping, mode).await.unwrap_or_else(|e| {
+                    warn!("Failed to capture SQL for {}: {}", mapping.source_table, e);
+                    Vec::new()
+                })
+            } else {
+                Vec::new()
+            };
+
+            // Auto-create or migrate target table from source schema if enabled
+            let manage_schema = self.config.sync.auto_create_tables
+                || self.config.sync.auto_migrate
+                || self.config.sync.strict_schema;
+            if manage_schema && !dry_run && schema_error.is_none() {
                 if let Err(e) = self.ensure_target_table(mapping).await {
-                    warn!(
-                        "Failed to create target table {}: {}",
-                        mapping.target_table, e
-                    );
-                    // Continue anyway - table might already exist with compatible schema
+                    if self.config.sync.strict_schema {
+                        schema_error = Some(e);
+                    } else {
+                        warn!(
+                            "Failed to create or migrate target table {}: {}",
+                            mapping.target_table, e
+                        );
+                        // Continue anyway - table might already exist with compatible schema
+                    }
+                }
+            }
+
+            for hook in &self.pre_table_hooks {
+                if let Err(e) = self.sink(|sink| hook(mapping, sink)) {
+                    warn!("Pre-table hook failed for {}: {}", mapping.source_table, e);
                 }
             }
 
             let table_start = Instant::now();
-            let result = self.sync_table(mapping, full_sync).await;
+            *self.table_retries.lock().unwrap_or_else(|e| e.into_inner()) = (0, None);
+            *self
+                .unchanged_rows
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = 0;
+            let span = info_span!(
+                "sync_table",
+                table = %mapping.source_table,
+                target = %mapping.target_table,
+                records = tracing::field::Empty,
+            );
+            let result = match schema_error {
+                Some(e) => Err(e),
+                None => {
+                    self.sync_table(mapping, mode, &cancel)
+                        .instrument(span.clone())
+                        .await
+                }
+            };
+            match result {
+                Ok((synced, _)) => {
+                    span.record("records", synced);
+                }
+                Err(_) => self.report_progress(SyncProgress {
+                    table: mapping.source_table.clone(),
+                    phase: SyncPhase::Failed,
+                    records_processed: 0,
+                    total_records: None,
+                    percent: 100,
+                    rows_per_second: None,
+                    eta: None,
+                }),
+            }
+            let (retries, last_retry_reason) =
+                std::mem::take(&mut *self.table_retries.lock().unwrap_or_else(|e| e.into_inner()));
+            let unchanged = std::mem::take(
+                &mut *self
+                    .unchanged_rows
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()),
+            );
+            self.log_oversized_values(mapping);
 
             let table_result = match result {
+                Err(Error::Cancelled { .. }) => {
+                    info!("Sync cancelled during {}", mapping.source_table);
+                    cancelled = true;
+                    break;
+                }
                 Ok((synced, failed)) => TableSyncResult {
                     source_table: mapping.source_table.clone(),
                     target_table: mapping.target_table.clone(),
                     success: true,
-                    records_synced: synced,
+                    records_synced: synced.saturating_sub(unchanged),
                     records_failed: failed,
+                    records_unchanged: unchanged,
                     duration_ms: table_start.elapsed().as_millis() as u64,
                     error: None,
+                    error_code: None,
+                    retryable: false,
+                    dry_run,
+                    sql,
+                    attempts: retries + 1,
+                    last_retry_reason,
                 },
                 Err(e) => {
                     overall_success = false;
                     error!("Failed to sync table {}: {}", mapping.source_table, e);
-                    TableSyncResult {
+                    let table_result = TableSyncResult {
                         source_table: mapping.source_table.clone(),
                         target_table: mapping.target_table.clone(),
                         success: false,
                         records_synced: 0,
                         records_failed: 0,
+                        records_unchanged: 0,
                         duration_ms: table_start.elapsed().as_millis() as u64,
                         error: Some(e.to_string()),
+                        error_code: Some(e.root_cause().code().to_string()),
+                        retryable: e.root_cause().is_retryable(),
+                        dry_run,
+                        sql,
+                        attempts: retries + 1,
+                        last_retry_reason,
+                    };
+                    if self.config.sync.failure_policy == FailurePolicy::Abort {
+                        aborted = Some((mapping.source_table.clone(), e));
                     }
+                    table_result
                 }
             };
 
+            if table_result.success && !dry_run {
+                let recorded = self.sink(|sink| {
+                    sink.record_sync(
+                        &mapping.target_table,
+                        table_result.records_synced,
+                        &mode.to_string(),
+                    )
+                });
+                if let Err(e) = recorded {
+                    warn!("Failed to record sync of {}: {}", mapping.target_table, e);
+                }
+
+                if self.config.sync.analyze_after && table_result.records_synced > 0 {
+                    self.analyze_target(mapping);
+                }
+            }
+
+            if let Some(ref mut checkpoint) = checkpoint {
+                if table_result.success {
+                    let watermark = self.stored_watermark(mapping, false).unwrap_or_default();
+                    if let Err(e) = checkpoint.complete(
+                        &mapping.source_table,
+                        table_result.records_synced,
+                        watermark,
+                    ) {
+                        warn!("Failed to write checkpoint: {}", e);
+                    }
+                }
+            }
+
+            for hook in &self.table_hooks {
+                if let Err(e) = self.sink(|sink| hook(mapping, &table_result, sink)) {
+                    warn!("Table hook failed for {}: {}", mapping.source_table, e);
+                }
+            }
+
+            if !dry_run {
+                self.metrics.record_table(
+                    &table_result.source_table,
+                    table_result.success,
+                    table_result.records_synced as u64,
+                    table_result.records_failed as u64,
+                    table_result.duration_ms,
+                );
+            }
             table_results.insert(mapping.source_table.clone(), table_result);
+
+            if aborted.is_some() {
+                break;
+            }
         }
 
         let duration_ms = start.elapsed().as_millis() as u64;
 
+        // A finished run starts the next one from scratch
+        if let Some(ref path) = self.config.sync.checkpoint_path {
+            if checkpoint.is_some() && overall_success && !cancelled {
+                Checkpoint::clear(path)?;
+            }
+        }
+
         let result = SyncResult {
             success: overall_success,
             mode: mode.to_string(),
             tables: table_results,
             duration_ms,
             completed_at: chrono::Utc::now().to_rfc3339(),
-            error: if overall_success {
+            error: if cancelled {
+                Some("Sync cancelled".into())
+            } else if let Some((ref table, _)) = aborted {
+                Some(format!("Sync aborted after {} failed", table))
+            } else if overall_success {
                 None
             } else {
                 Some("Some tables failed to sync".into())
             },
         };
 
+        if !dry_run {
+            self.metrics.record_sync(
+                overall_success,
+                result.total_records() as u64,
+                result.total_failed() as u64,
+                duration_ms,
+            );
+        }
+
+        if !dry_run {
+            if let Some(ref location) = self.config.sync.report_path {
+                match crate::report::write_report(location, &result).await {
+                    Ok(written) => info!("Wrote sync report to {}", written),
+                    Err(e) => warn!("Failed to write sync report: {}", e),
+                }
+            }
+        }
+
+        if cancelled {
+            warn!(
+                "Sync cancelled after {}ms. Synced: {}, Tables completed: {}",
+                duration_ms,
+                result.total_records(),
+                result.tables.len()
+            );
+            return Err(Error::Cancelled {
+                partial: Some(Box::new(result)),
+            });
+        }
+
+        if let Some((table, source)) = aborted {
+            warn!(
+                "Sync aborted after {} failed ({}ms). Synced: {}, Tables attempted: {}",
+                table,
+                duration_ms,
+                result.total_records(),
+                result.tables.len()
+            );
+            return Err(Error::Aborted {
+                table,
+                source: Box::new(source),
+                partial: Some(Box::new(result)),
+            });
+        }
+
         if overall_success {
             info!(
                 "Sync completed successfully in {}ms. Total records: {}, Tables synced: {}",
@@ -290,31 +1027,90 @@ impl SyncClient {
     #[instrument(skip(self), fields(source = %mapping.source_table, target = %mapping.target_table))]
     async fn ensure_target_table(&self, mapping: &TableMapping) -> Result<()> {
         // Check if table already exists
-        if self.md_client.table_exists(&mapping.target_table)? {
+        let exists = self.sink(|sink| sink.table_exists(&mapping.target_table))?;
+        if exists
+            && !self.config.sync.auto_migrate
+            && !self.config.sync.strict_schema
+            && !self.config.sync.stamp_columns
+            && !mapping.row_hash
+        {
             debug!("Target table {} already exists", mapping.target_table);
             return Ok(());
         }
+        if !exists && !self.config.sync.auto_create_tables {
+            debug!(
+                "Target table {} doesn't exist and auto_create_tables is off",
+                mapping.target_table
+            );
+            return Ok(());
+        }
 
-        // Introspect source table schema from PostgreSQL
-        info!("Introspecting schema for {}", mapping.source_table);
-        let columns = self
-            .pg_client
-            .introspect_table(&mapping.source_table)
-            .await?;
+        let columns = self.target_columns(mapping).await?;
 
-        if columns.is_empty() {
-            return Err(crate::error::Error::config(format!(
-                "Source table {} has no columns or doesn't exist",
-                mapping.source_table
-            )));
+        if exists {
+            let mismatches = self.sink(|sink| {
+                sink.check_schema_compatibility(
+                    &mapping.target_table,
+                    &columns,
+                    &mapping.type_overrides,
+                )
+            })?;
+            if !mismatches.is_empty() {
+                let details: Vec<String> = mismatches.iter().map(|m| m.to_string()).collect();
+                if self.config.sync.strict_schema {
+                    return Err(crate::error::Error::schema(format!(
+                        "Target table {} is incompatible with {}: {}",
+                        mapping.target_table,
+                        mapping.source_table,
+                        details.join("; ")
+                    )));
+                }
+                for detail in &details {
+                    warn!("Type mismatch in {}: {}", mapping.target_table, detail);
+                }
+            }
+
+            // Without auto_migrate, only the stamp and row hash columns are added
+            let migrated: Vec<IntrospectedColumn> = if self.config.sync.auto_migrate {
+                columns
+            } else {
+                columns
+                    .into_iter()
+                    .filter(|c| {
+                        [RUN_ID_COLUMN, SYNCED_AT_COLUMN, ROW_HASH_COLUMN]
+                            .contains(&c.name.as_str())
+                    })
+                    .collect()
+            };
+            if migrated.is_empty() {
+                return Ok(());
+            }
+            let added = self.sink(|sink| {
+                sink.reconcile_schema(&mapping.target_table, &migrated, &mapping.type_overrides)
+            })?;
+            if !added.is_empty() {
+                info!(
+                    "Added {} new columns to {}: {}",
+                    added.len(),
+                    mapping.target_table,
+                    added.join(", ")
+                );
+            }
+            return Ok(());
         }
 
-        // Create target table with matching schema
-        self.md_client.create_table_from_schema(
-            &mapping.target_table,
-            &columns,
-            &mapping.primary_key,
-        )?;
+        let indexes = self.source_indexes(mapping).await?;
+
+        // Create target table with matching schema and the source's indexes
+        self.sink(|sink| {
+            sink.ensure_table(
+                &mapping.target_table,
+                &columns,
+                &mapping.primary_key,
+                &indexes,
+                &mapping.type_overrides,
+            )
+        })?;
 
         info!(
             "Created target table {} with {} columns from source {}",
@@ -326,74 +1122,289 @@ impl SyncClient {
         Ok(())
     }
 
-    /// Sync a single table.
-    #[instrument(skip(self), fields(table = %mapping.source_table))]
-    async fn sync_table(&self, mapping: &TableMapping, full_sync: bool) -> Result<(usize, usize)> {
-        self.report_progress(SyncProgress {
-            table: mapping.source_table.clone(),
-            phase: SyncPhase::Fetching,
-            records_processed: 0,
-            total_records: None,
-            percent: 0,
-        });
+    /// Fail with [`Error::SourceNotFound`] if the mapping's source table doesn't
+    /// exist. Query-backed mappings have no table to check.
+    async fn check_source_exists(&self, mapping: &TableMapping) -> Result<()> {
+        if mapping.source_query.is_some() {
+            return Ok(());
+        }
+        if !self.source.table_exists(&mapping.source_table).await? {
+            return Err(Error::source_not_found(&mapping.source_table));
+        }
+        Ok(())
+    }
 
-        // Fetch rows from PostgreSQL
-        let limit = if self.config.sync.max_records > 0 {
-            Some(self.config.sync.max_records)
-        } else {
-            None
+    /// Fail with [`Error::Validation`] unless the sync flag column an incremental
+    /// sync selects on (`WHERE NOT flag`) and marks exists and is boolean.
+    ///
+    /// Watermark tables don't use the flag. A source reporting no columns fails
+    /// too, as the flag can't be found.
+    async fn check_sync_flag(&self, mapping: &TableMapping) -> Result<()> {
+        let Some(ref flag) = mapping.sync_flag_column else {
+            return Ok(());
         };
-
-        let rows = self.pg_client.fetch_rows(mapping, full_sync, limit).await?;
-        let total = rows.len();
-
-        if total == 0 {
-            info!("No rows to sync for {}", mapping.source_table);
-            return Ok((0, 0));
+        if mapping.watermark_column.is_some() {
+            return Ok(());
         }
 
-        info!("Fetched {} rows from {}", total, mapping.source_table);
+        let columns = self.source.introspect_source(mapping).await?;
+        match columns.iter().find(|c| &c.name == flag) {
+            None => Err(Error::validation(format!(
+                "Sync flag column {} not found in {}; add it as a boolean column or set watermark_column",
+                flag, mapping.source_table
+            ))),
+            Some(column) if !matches!(column.pg_type.as_str(), "boolean" | "bool") => {
+                Err(Error::validation(format!(
+                    "Sync flag column {} of {} is {}, not boolean",
+                    flag, mapping.source_table, column.pg_type
+                )))
+            }
+            Some(_) => Ok(()),
+        }
+    }
 
-        self.report_progress(SyncProgress {
-            table: mapping.source_table.clone(),
-            phase: SyncPhase::Inserting,
-            records_processed: 0,
-            total_records: Some(total),
-            percent: 25,
+    /// Introspect the source columns the target table should have.
+    ///
+    /// The sync flag and excluded columns are dropped and hashed columns become
+    /// text. Fails if the
+    /// source has no columns or lacks a primary key column.
+    async fn target_columns(&self, mapping: &TableMapping) -> Result<Vec<IntrospectedColumn>> {
+        info!("Introspecting schema for {}", mapping.source_table);
+        let mut columns = self.source.introspect_source(mapping).await?;
+        // The sync flag is internal to PostgreSQL
+        columns.retain(|c| {
+            mapping.sync_flag_column.as_ref() != Some(&c.name) && !mapping.is_excluded(&c.name)
         });
+        // Hashes are text whatever the source column was
+        for column in &mut columns {
+            if mapping
+                .transforms
+                .get(&column.name)
+                .is_some_and(|t| t.produces_text())
+            {
+                column.pg_type = "text".to_string();
+            }
+        }
 
-        // Insert to MotherDuck
-        let synced = if self.config.sync.use_transactions {
-            self.md_client
-                .batch_upsert(mapping, &rows, self.config.sync.batch_size)?
-        } else {
-            self.md_client.upsert_rows(mapping, &rows)?
-        };
-
-        let failed = total - synced;
-
-        // Mark as synced in PostgreSQL
-        if self.config.sync.mark_synced && !full_sync && synced > 0 {
+        if columns.is_empty() {
+            return Err(crate::error::Error::config(format!(
+                "Source table {} has no columns or doesn't exist",
+                mapping.source_table
+            )));
+        }
+
+        let missing: Vec<&str> = mapping
+            .primary_key
+            .iter()
+            .filter(|pk| !columns.iter().any(|c| &c.name == *pk))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(crate::error::Error::validation(format!(
+                "Primary key column(s) {} of {} not found in source",
+                missing.join(", "),
+                mapping.source_table
+            )));
+        }
+
+        if mapping.row_hash {
+            add_generated_columns(
+                mapping,
+                &mut columns,
+                &[(ROW_HASH_COLUMN, "text")],
+                "row_hash",
+            )?;
+        }
+        if self.config.sync.stamp_columns {
+            add_stamp_columns(mapping, &mut columns)?;
+        }
+
+        Ok(columns)
+    }
+
+    /// Build the SQL a sync of `mapping` runs, without running any of it.
+    async fn preview_sql(&self, mapping: &TableMapping, mode: SyncMode) -> Result<Vec<String>> {
+        let columns = self.target_columns(mapping).await?;
+        let indexes = self.source_indexes(mapping).await?;
+        let create = self.config.sync.auto_create_tables;
+        let full_sync = mode == SyncMode::Full;
+        let has_since = self
+            .stored_watermark(mapping, full_sync)
+            .unwrap_or_default()
+            .is_some();
+        let mut sql: Vec<String> = self
+            .source
+            .fetch_statement(mapping, full_sync, has_since)
+            .into_iter()
+            .collect();
+        sql.extend(self.sink(|sink| sink.preview_writes(mapping, &columns, &indexes, create))?);
+        if self.marks_synced(mapping, full_sync) {
+            sql.extend(self.source.mark_synced_statement(mapping));
+        }
+
+        for statement in &sql {
+            trace!("SQL for {}: {}", mapping.source_table, statement);
+        }
+        Ok(sql)
+    }
+
+    /// Indexes of the source table; a query's result has no indexes of its own.
+    ///
+    /// Indexes on the sync flag are left out, as the target has no such column.
+    async fn source_indexes(&self, mapping: &TableMapping) -> Result<Vec<Index>> {
+        let mut indexes = match mapping.source_query {
+            Some(_) => Vec::new(),
+            None => {
+                self.source
+                    .introspect_indexes(&mapping.source_table)
+                    .await?
+            }
+        };
+        if let Some(ref flag) = mapping.sync_flag_column {
+            indexes.retain(|index| !index.columns.contains(flag));
+        }
+        Ok(indexes)
+    }
+
+    /// Sync a single table.
+    async fn sync_table(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let full_sync = mode == SyncMode::Full;
+        if !full_sync {
+            self.check_sync_flag(mapping).await?;
+        }
+
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Fetching,
+            records_processed: 0,
+            total_records: None,
+            percent: 0,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        // Fetch rows from PostgreSQL
+        let limit = if self.config.sync.max_records > 0 {
+            Some(self.config.sync.max_records)
+        } else {
+            None
+        };
+
+        let since = self.stored_watermark(mapping, full_sync)?;
+
+        if full_sync && mapping.full_refresh_strategy == FullRefreshStrategy::AtomicSwap {
+            return self.sync_table_swap(mapping, mode, limit, cancel).await;
+        }
+
+        if full_sync && self.config.sync.bulk_copy {
+            if cancel.is_cancelled() {
+                return Err(Error::cancelled());
+            }
+            if let Some(synced) = self.bulk_copy(mapping, limit).await? {
+                self.delete_missing_rows(mapping, None).await?;
+                self.run_post_sql(mapping, synced)?;
+                self.report_progress(SyncProgress {
+                    table: mapping.source_table.clone(),
+                    phase: SyncPhase::Completed,
+                    records_processed: synced,
+                    total_records: Some(synced),
+                    percent: 100,
+                    rows_per_second: None,
+                    eta: None,
+                });
+                info!("Bulk copied {} rows to {}", synced, mapping.target_table);
+                return Ok((synced, 0));
+            }
+        }
+
+        if mapping.pagination == Pagination::Keyset {
+            return self
+                .sync_table_keyset(mapping, mode, limit, since, cancel)
+                .await;
+        }
+
+        if self.config.sync.streaming {
+            return self
+                .sync_table_streaming(mapping, mode, limit, since.as_ref(), cancel)
+                .await;
+        }
+
+        let mut rows = self
+            .retry("fetch", || {
+                self.source
+                    .fetch_rows(mapping, full_sync, limit, since.as_ref())
+            })
+            .await?;
+        self.prepare_rows(mapping, &mut rows);
+        let total = rows.len();
+
+        let mut watermark = Watermark::default();
+        advance_watermark(&mut watermark, mapping, &rows, &self.config.sync);
+
+        // A full, unlimited fetch already holds every live key
+        let live_keys = (full_sync && limit.is_none()).then(|| {
+            rows.iter()
+                .filter_map(|r| primary_key_values(r, &mapping.primary_key))
+                .collect()
+        });
+
+        if total == 0 {
+            info!("No rows to sync for {}", mapping.source_table);
+            if mode != SyncMode::DryRun {
+                self.delete_missing_rows(mapping, live_keys).await?;
+            }
+            return Ok((0, 0));
+        }
+
+        info!("Fetched {} rows from {}", total, mapping.source_table);
+
+        if mode == SyncMode::DryRun {
+            info!(
+                "Dry run: would sync {} rows to {}",
+                total, mapping.target_table
+            );
             self.report_progress(SyncProgress {
                 table: mapping.source_table.clone(),
-                phase: SyncPhase::Marking,
-                records_processed: synced,
+                phase: SyncPhase::Completed,
+                records_processed: total,
                 total_records: Some(total),
-                percent: 75,
+                percent: 100,
+                rows_per_second: None,
+                eta: None,
             });
+            return Ok((total, 0));
+        }
 
-            let pk_col = &mapping.primary_key[0];
-            let ids: Vec<String> = rows
-                .iter()
-                .filter_map(|r| r.get(pk_col))
-                .map(|v| match v {
-                    serde_json::Value::String(s) => s.clone(),
-                    other => other.to_string().trim_matches('"').to_string(),
-                })
-                .collect();
+        if cancel.is_cancelled() {
+            return Err(Error::cancelled());
+        }
+
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Inserting,
+            records_processed: 0,
+            total_records: Some(total),
+            percent: 25,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        let synced = self
+            .write_rows(mapping, &rows, full_sync, Some(total), Marks::Source)
+            .await?;
+        let failed = total - synced;
 
-            self.pg_client.mark_synced(mapping, &ids).await?;
+        self.delete_missing_rows(mapping, live_keys).await?;
+        // A per-table transaction already ran it with the upsert
+        if !self.table_transaction() {
+            self.run_post_sql(mapping, synced)?;
         }
+        self.store_watermark(mapping, mode, watermark.value(), synced)?;
 
         self.report_progress(SyncProgress {
             table: mapping.source_table.clone(),
@@ -401,6 +1412,8 @@ impl SyncClient {
             records_processed: synced,
             total_records: Some(total),
             percent: 100,
+            rows_per_second: None,
+            eta: None,
         });
 
         info!(
@@ -411,87 +1424,3559 @@ impl SyncClient {
         Ok((synced, failed))
     }
 
-    /// Report progress via callback.
-    fn report_progress(&self, progress: SyncProgress) {
-        if let Some(ref callback) = self.progress_callback {
-            callback(progress);
-        }
+    /// Full refresh through a staging table swapped in for the target
+    /// (`full_refresh_strategy = "atomic_swap"`).
+    ///
+    /// `<target>__staging` is created from the source schema and loaded with the
+    /// Appender, batch by batch from a cursor. The target is only replaced once
+    /// every row is in; on failure the staging table is dropped and the target
+    /// is left as it was.
+    async fn sync_table_swap(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        limit: Option<usize>,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let staging = format!("{}__staging", mapping.target_table);
+        let columns = self.target_columns(mapping).await?;
+        let indexes = self.source_indexes(mapping).await?;
+        self.sink(|sink| {
+            sink.drop_table(&staging)?;
+            sink.ensure_table(
+                &staging,
+                &columns,
+                &mapping.primary_key,
+                &[],
+                &mapping.type_overrides,
+            )
+        })?;
+
+        let staging_mapping = TableMapping {
+            target_table: staging.clone(),
+            post_sql: None,
+            ..mapping.clone()
+        };
+        let mut watermark = Watermark::default();
+        let swapped = match self
+            .load_staging(&staging_mapping, limit, &mut watermark, cancel)
+            .await
+        {
+            Ok(loaded) => self
+                .sink(|sink| sink.swap_table(&staging, &mapping.target_table, &indexes))
+                .map(|_| loaded),
+            Err(e) => Err(e),
+        };
+
+        let (synced, skipped) = match swapped {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                if let Err(drop_err) = self.sink(|sink| sink.drop_table(&staging)) {
+                    warn!("Failed to drop {}: {}", staging, drop_err);
+                }
+                return Err(e);
+            }
+        };
+        self.run_post_sql(mapping, synced)?;
+        self.store_watermark(mapping, mode, watermark.value(), synced)?;
+
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Completed,
+            records_processed: synced,
+            total_records: Some(synced),
+            percent: 100,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        info!(
+            "Refreshed {} with {} rows through {}",
+            mapping.target_table, synced, staging
+        );
+        Ok((synced, skipped))
     }
 
-    /// Get unsynced counts for all tables.
-    pub async fn get_unsynced_counts(&self) -> Result<HashMap<String, i64>> {
-        let mut counts = HashMap::new();
+    /// Append every source row to the staging table of `sync_table_swap`.
+    ///
+    /// Returns the number of rows loaded and the number skipped for a NULL
+    /// primary key.
+    async fn load_staging(
+        &self,
+        staging: &TableMapping,
+        limit: Option<usize>,
+        watermark: &mut Watermark,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let mut cursor = self
+            .retry("open cursor", || {
+                self.source.open_cursor(staging, true, limit, None)
+            })
+            .await?;
+
+        let start = Instant::now();
+        let mut loaded = 0;
+        let mut skipped = 0;
+        loop {
+            if cancel.is_cancelled() {
+                cursor.abort().await;
+                return Err(Error::cancelled());
+            }
 
-        for mapping in &self.config.tables {
-            if mapping.enabled {
-                let count = self.pg_client.unsynced_count(mapping).await?;
-                counts.insert(mapping.source_table.clone(), count);
+            let batch = cursor
+                .next_batch_bounded(
+                    staging.batch_size_or(self.config.sync.batch_size),
+                    self.max_batch_bytes(),
+                )
+                .await;
+            let mut rows = match batch {
+                Ok(rows) if rows.is_empty() => break,
+                Ok(rows) => rows,
+                Err(e) => {
+                    cursor.abort().await;
+                    return Err(e);
+                }
+            };
+            self.prepare_rows(staging, &mut rows);
+            advance_watermark(watermark, staging, &rows, &self.config.sync);
+            let fetched = rows.len();
+            self.log_rejected_rows(staging, &rows);
+            rows.retain(|r| !rejected_row(staging, r, &self.config.sync));
+            skipped += fetched - rows.len();
+            // The swapped-in table simply leaves soft-deleted rows out
+            rows.retain(|r| !staging.is_soft_deleted(r));
+
+            if let Err(e) = self.sink(|sink| sink.append_rows(staging, &rows)) {
+                cursor.abort().await;
+                return Err(e);
             }
+            loaded += rows.len();
+
+            debug!("Staged {} rows for {}", loaded, staging.source_table);
+            self.report_progress(SyncProgress {
+                table: staging.source_table.clone(),
+                phase: SyncPhase::Inserting,
+                records_processed: loaded,
+                total_records: None,
+                percent: 50,
+                rows_per_second: throughput(loaded, start.elapsed()),
+                eta: None,
+            });
         }
 
-        Ok(counts)
+        cursor.close().await?;
+        Ok((loaded, skipped))
     }
-}
 
-/// Create exponential backoff from config.
-pub fn create_backoff(config: &crate::config::RetryConfig) -> ExponentialBackoff {
-    ExponentialBackoffBuilder::new()
-        .with_initial_interval(config.initial_backoff())
-        .with_max_interval(config.max_backoff())
-        .with_multiplier(config.multiplier)
-        .with_max_elapsed_time(Some(Duration::from_secs(300)))
-        .build()
-}
+    /// Load a full sync through a CSV file instead of row by row (`sync.bulk_copy`).
+    ///
+    /// Returns `None` when the table has to take the row path instead: see
+    /// [`bulk_copy_blocker`], plus array or binary columns, whose PostgreSQL
+    /// text form DuckDB can't cast, and any failure while copying or loading.
+    async fn bulk_copy(
+        &self,
+        mapping: &TableMapping,
+        limit: Option<usize>,
+    ) -> Result<Option<usize>> {
+        if let Some(reason) = bulk_copy_blocker(mapping, &self.config.sync) {
+            debug!("Bulk copy skipped for {}: {}", mapping.source_table, reason);
+            return Ok(None);
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let mut columns = self.source.introspect_source(mapping).await?;
+        columns.retain(|c| {
+            mapping.sync_flag_column.as_ref() != Some(&c.name)
+                && !mapping.is_excluded(&c.name)
+                && (mapping.columns.is_empty()
+                    || mapping.columns.contains(&c.name)
+                    || mapping.primary_key.contains(&c.name))
+        });
+        if let Some(column) = columns.iter().find(|c| {
+            matches!(
+                ColumnType::from_postgres(&c.pg_type),
+                ColumnType::Array(_) | ColumnType::Blob
+            )
+        }) {
+            debug!(
+                "Bulk copy skipped for {}: column {} is {}",
+                mapping.source_table, column.name, column.pg_type
+            );
+            return Ok(None);
+        }
+        let columns: Vec<String> = columns.into_iter().map(|c| c.name).collect();
 
-    #[test]
-    fn test_sync_mode_display() {
-        assert_eq!(SyncMode::Incremental.to_string(), "incremental");
-        assert_eq!(SyncMode::Full.to_string(), "full");
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Inserting,
+            records_processed: 0,
+            total_records: None,
+            percent: 25,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        let path = bulk_copy_path(&mapping.target_table);
+        let loaded = self.copy_and_load(mapping, &columns, limit, &path).await;
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+
+        match loaded {
+            Ok(rows) => Ok(Some(rows)),
+            Err(e) => {
+                warn!(
+                    "Bulk copy of {} failed, falling back to row-by-row sync: {}",
+                    mapping.source_table, e
+                );
+                Ok(None)
+            }
+        }
     }
 
-    #[test]
-    fn test_sync_result_totals() {
-        let mut tables = HashMap::new();
-        tables.insert(
-            "table1".to_string(),
-            TableSyncResult {
-                source_table: "table1".to_string(),
-                target_table: "table1".to_string(),
-                success: true,
-                records_synced: 100,
-                records_failed: 5,
-                duration_ms: 1000,
-                error: None,
-            },
+    /// Copy `columns` of the source into the CSV file at `path`, then load it into the target.
+    async fn copy_and_load(
+        &self,
+        mapping: &TableMapping,
+        columns: &[String],
+        limit: Option<usize>,
+        path: &Path,
+    ) -> Result<usize> {
+        let bytes = self
+            .source
+            .copy_to_csv(mapping, columns, limit, path)
+            .await?;
+        debug!(
+            "Staged {} bytes of {} in {}",
+            bytes,
+            mapping.source_table,
+            path.display()
         );
-        tables.insert(
-            "table2".to_string(),
-            TableSyncResult {
-                source_table: "table2".to_string(),
-                target_table: "table2".to_string(),
-                success: true,
-                records_synced: 50,
-                records_failed: 0,
-                duration_ms: 500,
-                error: None,
-            },
+
+        let path = path.to_string_lossy();
+        self.sink(|sink| sink.load_csv(mapping, columns, &path))
+    }
+
+    /// Sync a single table by streaming rows through a server-side cursor.
+    ///
+    /// Each `batch_size` chunk is upserted and marked before the next one is
+    /// fetched, so memory stays flat regardless of table size. Marks are committed
+    /// when the cursor closes; if the sync fails midway they roll back and the
+    /// already-upserted rows are simply replaced on the next run.
+    async fn sync_table_streaming(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        limit: Option<usize>,
+        since: Option<&serde_json::Value>,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let full_sync = mode == SyncMode::Full;
+        let complete = full_sync && limit.is_none();
+
+        let mut cursor = self
+            .retry("open cursor", || {
+                self.source.open_cursor(mapping, full_sync, limit, since)
+            })
+            .await?;
+
+        // With a per-table transaction every streamed batch commits together
+        let table_txn = self.table_transaction() && mode != SyncMode::DryRun;
+        if table_txn {
+            if let Err(e) = self.sink(|sink| sink.begin(&mapping.target_table)) {
+                cursor.abort().await;
+                return Err(e);
+            }
+        }
+
+        let mut live_keys = Vec::new();
+        let mut watermark = Watermark::default();
+        let streamed = self
+            .stream_batches(
+                mapping,
+                mode,
+                cursor.as_mut(),
+                complete.then_some(&mut live_keys),
+                &mut watermark,
+                cancel,
+            )
+            .await;
+
+        let (total, synced) = match streamed {
+            Ok(counts) => {
+                // MotherDuck commits first so marks never outlive rolled-back rows
+                if table_txn {
+                    if let Err(e) = self.run_post_sql(mapping, counts.1) {
+                        self.sink(|sink| sink.rollback());
+                        cursor.abort().await;
+                        return Err(e);
+                    }
+                    if let Err(e) = self.sink(|sink| sink.commit(&mapping.target_table)) {
+                        cursor.abort().await;
+                        return Err(e);
+                    }
+                }
+                cursor.close().await?;
+                counts
+            }
+            Err(e @ Error::Cancelled { .. }) if !table_txn => {
+                // Keep the marks for batches already written
+                cursor.close().await?;
+                return Err(e);
+            }
+            Err(e) => {
+                if table_txn {
+                    self.sink(|sink| sink.rollback());
+                }
+                cursor.abort().await;
+                return Err(e);
+            }
+        };
+
+        if mode == SyncMode::DryRun {
+            info!(
+                "Dry run: would sync {} rows to {}",
+                total, mapping.target_table
+            );
+            return Ok((total, 0));
+        }
+
+        self.delete_missing_rows(mapping, complete.then_some(live_keys))
+            .await?;
+        if !table_txn {
+            self.run_post_sql(mapping, synced)?;
+        }
+        self.store_watermark(mapping, mode, watermark.value(), synced)?;
+
+        let failed = total - synced;
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Completed,
+            records_processed: synced,
+            total_records: Some(total),
+            percent: 100,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        info!(
+            "Streamed {} rows to {} ({} failed)",
+            synced, mapping.target_table, failed
         );
 
-        let result = SyncResult {
-            success: true,
-            mode: "incremental".to_string(),
-            tables,
-            duration_ms: 1500,
-            completed_at: "2024-01-01T00:00:00Z".to_string(),
-            error: None,
+        Ok((synced, failed))
+    }
+
+    /// Sync a single table page by page in primary key order (`pagination = "keyset"`).
+    ///
+    /// Each page of `batch_size` rows starts after the last key of the previous
+    /// one and is written, and marked, before the next is fetched, so memory
+    /// stays flat without holding a cursor open. Tables that
+    /// [resume by key](TableMapping::resumes_by_key) store the last key after
+    /// every page, so an interrupted run picks up where it stopped.
+    async fn sync_table_keyset(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        limit: Option<usize>,
+        since: Option<serde_json::Value>,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let full_sync = mode == SyncMode::Full;
+        let complete = full_sync && limit.is_none();
+        let page_size = mapping.batch_size_or(self.config.sync.batch_size);
+        let start = Instant::now();
+
+        // Resuming by key, the stored "watermark" is where the last run stopped
+        let (since, mut after) = if mapping.resumes_by_key() {
+            (None, since)
+        } else {
+            (since, None)
+        };
+        let mut live_keys = Vec::new();
+        let mut watermark = Watermark::default();
+        let mut total = 0;
+        let mut synced = 0;
+        // Pages are written without post_sql, which runs once after the last
+        let page_mapping = TableMapping {
+            post_sql: None,
+            ..mapping.clone()
         };
 
-        assert_eq!(result.total_records(), 150);
-        assert_eq!(result.total_failed(), 5);
-        assert!(result.all_tables_success());
+        loop {
+            if cancel.is_cancelled() {
+                info!(
+                    "Stopping {} after {} paged rows",
+                    mapping.source_table, total
+                );
+                return Err(Error::cancelled());
+            }
+
+            let page_limit = match limit {
+                Some(limit) if limit <= total => break,
+                Some(limit) => page_size.min(limit - total),
+                None => page_size,
+            };
+            let mut rows = self
+                .retry("fetch", || {
+                    self.source.fetch_page(
+                        mapping,
+                        full_sync,
+                        page_limit,
+                        since.as_ref(),
+                        after.as_ref(),
+                    )
+                })
+                .await?;
+            let Some(last) = rows.last() else {
+                break;
+            };
+            // Taken before transforms, which may rewrite key columns
+            let Some(last_key) = keyset_position(mapping, last) else {
+                return Err(Error::sync(
+                    format!(
+                        "{} returned a row without its primary key",
+                        mapping.source_table
+                    ),
+                    0,
+                ));
+            };
+            let fetched = rows.len();
+            self.prepare_rows(mapping, &mut rows);
+            total += fetched;
+            advance_watermark(&mut watermark, mapping, &rows, &self.config.sync);
+            if complete {
+                live_keys.extend(
+                    rows.iter()
+                        .filter_map(|r| primary_key_values(r, &mapping.primary_key)),
+                );
+            }
+
+            if mode != SyncMode::DryRun {
+                synced += self
+                    .write_rows(&page_mapping, &rows, full_sync, None, Marks::Source)
+                    .await?;
+                if mapping.resumes_by_key() {
+                    self.store_watermark(mapping, mode, Some(&last_key), synced)?;
+                }
+            }
+            after = Some(last_key);
+
+            debug!("Paged {} rows from {}", total, mapping.source_table);
+            self.report_progress(SyncProgress {
+                table: mapping.source_table.clone(),
+                phase: SyncPhase::Inserting,
+                records_processed: total,
+                total_records: None,
+                percent: 50,
+                rows_per_second: throughput(total, start.elapsed()),
+                eta: None,
+            });
+
+            if fetched < page_limit {
+                break;
+            }
+        }
+
+        if mode == SyncMode::DryRun {
+            info!(
+                "Dry run: would sync {} rows to {}",
+                total, mapping.target_table
+            );
+            return Ok((total, 0));
+        }
+
+        self.delete_missing_rows(mapping, complete.then_some(live_keys))
+            .await?;
+        self.run_post_sql(mapping, synced)?;
+        self.store_watermark(mapping, mode, watermark.value(), synced)?;
+
+        let failed = total - synced;
+        self.report_progress(SyncProgress {
+            table: mapping.source_table.clone(),
+            phase: SyncPhase::Completed,
+            records_processed: synced,
+            total_records: Some(total),
+            percent: 100,
+            rows_per_second: None,
+            eta: None,
+        });
+
+        info!(
+            "Paged {} rows to {} ({} failed)",
+            synced, mapping.target_table, failed
+        );
+
+        Ok((synced, failed))
+    }
+
+    /// Refresh the target's planner statistics after it synced (`sync.analyze_after`).
+    ///
+    /// Failures are logged, never fatal: the rows are already written.
+    fn analyze_target(&self, mapping: &TableMapping) {
+        let start = Instant::now();
+        let analyzed: Result<()> = self.sink(|sink| {
+            sink.analyze_table(&mapping.target_table)?;
+            if self.config.sync.checkpoint_after {
+                sink.checkpoint()?;
+            }
+            Ok(())
+        });
+        match analyzed {
+            Ok(()) => info!(
+                "Analyzed {} in {}ms",
+                mapping.target_table,
+                start.elapsed().as_millis()
+            ),
+            Err(e) => warn!("Failed to analyze {}: {}", mapping.target_table, e),
+        }
+    }
+
+    /// Run the mapping's `post_sql` once the table has written rows.
+    fn run_post_sql(&self, mapping: &TableMapping, synced: usize) -> Result<()> {
+        if mapping.post_sql.is_none() || synced == 0 {
+            return Ok(());
+        }
+        self.sink(|sink| sink.run_post_sql(mapping))?;
+        info!("Ran post_sql for {}", mapping.target_table);
+        Ok(())
+    }
+
+    /// Apply the mapping's transforms to fetched rows, turn empty strings into
+    /// NULL with `sync.empty_string_as_null`, truncate or null values over
+    /// `sync.max_field_bytes`, hash them for tables with `row_hash` and add the
+    /// run stamp with `sync.stamp_columns`.
+    fn prepare_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &mut [HashMap<String, serde_json::Value>],
+    ) {
+        mapping.apply_transforms(rows);
+        if self.config.sync.empty_string_as_null {
+            empty_strings_to_null(mapping, rows);
+        }
+        if let Some(max_bytes) = self.config.sync.max_field_bytes {
+            limit_field_sizes(
+                mapping,
+                rows,
+                max_bytes,
+                self.config.sync.oversized_fields,
+                &mut self
+                    .oversized_values
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner()),
+            );
+        }
+        // Before stamping, which changes every run
+        if mapping.row_hash {
+            hash_rows(rows);
+        }
+        if self.config.sync.stamp_columns {
+            let (run_id, synced_at) = self
+                .run_stamp
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone();
+            stamp_rows(rows, &run_id, &synced_at);
+        }
+    }
+
+    /// Log the rows of `rows` that [`rejected_row`] rejects as they are skipped:
+    /// each one with a NULL key, and oversized ones in the table's summary.
+    fn log_rejected_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, serde_json::Value>],
+    ) {
+        let mut oversized = self
+            .oversized_values
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        for row in rows {
+            if null_key(mapping, row) {
+                log_null_key(mapping, row);
+            } else if let Some(column) = oversized_column(mapping, row, &self.config.sync) {
+                *oversized
+                    .entry((column.clone(), "skipped with their rows"))
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    /// Log how many values of each column of `mapping` were over
+    /// `sync.max_field_bytes` and what became of them, then start counting afresh.
+    fn log_oversized_values(&self, mapping: &TableMapping) {
+        let oversized = std::mem::take(
+            &mut *self
+                .oversized_values
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()),
+        );
+        for ((column, outcome), count) in oversized {
+            warn!(
+                "{}: {} values of {} over max_field_bytes ({}) were {}",
+                mapping.source_table,
+                count,
+                column,
+                self.config.sync.max_field_bytes.unwrap_or_default(),
+                outcome
+            );
+        }
+    }
+
+    /// Memory a streamed batch may hold before it is written (`sync.max_memory_mb`).
+    fn max_batch_bytes(&self) -> Option<usize> {
+        self.config.sync.max_memory_mb.map(|mb| mb * 1024 * 1024)
+    }
+
+    /// Whether all batches of a table share one MotherDuck transaction.
+    fn table_transaction(&self) -> bool {
+        self.config.sync.use_transactions
+            && self.config.sync.transaction_scope == TransactionScope::PerTable
+    }
+
+    /// Drain a cursor in `batch_size` chunks, writing each chunk as it arrives.
+    ///
+    /// Returns the number of rows fetched and the number written. When `live_keys`
+    /// is given, every fetched primary key is collected into it. `watermark` is
+    /// advanced past the rows written (see [`advance_watermark`]).
+    async fn stream_batches(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        cursor: &mut dyn SourceCursor,
+        mut live_keys: Option<&mut Vec<Vec<serde_json::Value>>>,
+        watermark: &mut Watermark,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        if self.config.sync.pipeline_depth > 0 && mode != SyncMode::DryRun {
+            if multi_threaded_runtime() {
+                return self
+                    .stream_batches_pipelined(mapping, mode, cursor, live_keys, watermark, cancel)
+                    .await;
+            }
+            debug!(
+                "pipeline_depth needs a multi-threaded runtime; streaming {} serially",
+                mapping.source_table
+            );
+        }
+
+        let full_sync = mode == SyncMode::Full;
+        let start = Instant::now();
+        let mut total = 0;
+        let mut synced = 0;
+
+        loop {
+            if cancel.is_cancelled() {
+                info!(
+                    "Stopping {} after {} streamed rows",
+                    mapping.source_table, total
+                );
+                return Err(Error::cancelled());
+            }
+
+            let mut rows = cursor
+                .next_batch_bounded(
+                    mapping.batch_size_or(self.config.sync.batch_size),
+                    self.max_batch_bytes(),
+                )
+                .await?;
+            if rows.is_empty() {
+                break;
+            }
+            self.prepare_rows(mapping, &mut rows);
+            total += rows.len();
+            advance_watermark(watermark, mapping, &rows, &self.config.sync);
+
+            if let Some(keys) = live_keys.as_deref_mut() {
+                keys.extend(
+                    rows.iter()
+                        .filter_map(|r| primary_key_values(r, &mapping.primary_key)),
+                );
+            }
+
+            if mode != SyncMode::DryRun {
+                synced += self
+                    .write_rows(mapping, &rows, full_sync, None, Marks::Cursor(&*cursor))
+                    .await?;
+            }
+
+            debug!("Streamed {} rows from {}", total, mapping.source_table);
+            self.report_progress(SyncProgress {
+                table: mapping.source_table.clone(),
+                phase: SyncPhase::Inserting,
+                records_processed: total,
+                total_records: None,
+                percent: 50,
+                rows_per_second: throughput(total, start.elapsed()),
+                eta: None,
+            });
+        }
+
+        Ok((total, synced))
+    }
+
+    /// [`stream_batches`](Self::stream_batches), fetching the next batches while
+    /// earlier ones are written (`sync.pipeline_depth`).
+    ///
+    /// The fetch side reads the cursor into a channel holding up to
+    /// `pipeline_depth` batches, and a writer on its own thread drains it. DuckDB
+    /// writes block their thread, so the writer can't share this task. The
+    /// cursor is busy fetching, so the writer queues the keys it wrote and the
+    /// fetch side marks them between fetches. Once cancelled, batches already
+    /// fetched are still written and marked before the cancellation is returned.
+    ///
+    /// Needs a multi-threaded runtime, where the current worker can block.
+    async fn stream_batches_pipelined(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        cursor: &mut dyn SourceCursor,
+        mut live_keys: Option<&mut Vec<Vec<serde_json::Value>>>,
+        watermark: &mut Watermark,
+        cancel: &CancellationToken,
+    ) -> Result<(usize, usize)> {
+        let full_sync = mode == SyncMode::Full;
+        let start = Instant::now();
+        let (batches, mut fetched) = tokio::sync::mpsc::channel(self.config.sync.pipeline_depth);
+        let (queue, mut marks) = tokio::sync::mpsc::unbounded_channel::<Vec<Vec<_>>>();
+        let mut cancelled = false;
+
+        let fetch = async {
+            let batches = batches;
+            let mut total = 0;
+            loop {
+                while let Ok(keys) = marks.try_recv() {
+                    cursor.mark_synced(&keys).await?;
+                }
+                if cancel.is_cancelled() {
+                    info!(
+                        "Stopping {} after {} streamed rows",
+                        mapping.source_table, total
+                    );
+                    cancelled = true;
+                    break;
+                }
+
+                let mut rows = cursor
+                    .next_batch_bounded(
+                        mapping.batch_size_or(self.config.sync.batch_size),
+                        self.max_batch_bytes(),
+                    )
+                    .await?;
+                if rows.is_empty() {
+                    break;
+                }
+                self.prepare_rows(mapping, &mut rows);
+                total += rows.len();
+                advance_watermark(watermark, mapping, &rows, &self.config.sync);
+
+                if let Some(keys) = live_keys.as_deref_mut() {
+                    keys.extend(
+                        rows.iter()
+                            .filter_map(|r| primary_key_values(r, &mapping.primary_key)),
+                    );
+                }
+                debug!("Streamed {} rows from {}", total, mapping.source_table);
+                // The write task only stops early on an error, which ends the join
+                if batches.send(rows).await.is_err() {
+                    break;
+                }
+            }
+            Ok::<_, Error>(total)
+        };
+
+        // Owns the receiver, so a failed writer stops the fetch side's sends
+        let queue = &queue;
+        let write = async move {
+            let mut written = 0;
+            let mut synced = 0;
+            while let Some(rows) = fetched.recv().await {
+                synced += self
+                    .write_rows(mapping, &rows, full_sync, None, Marks::Queued(queue))
+                    .await?;
+                written += rows.len();
+                self.report_progress(SyncProgress {
+                    table: mapping.source_table.clone(),
+                    phase: SyncPhase::Inserting,
+                    records_processed: written,
+                    total_records: None,
+                    percent: 50,
+                    rows_per_second: throughput(written, start.elapsed()),
+                    eta: None,
+                });
+            }
+            Ok::<_, Error>(synced)
+        };
+
+        let runtime = tokio::runtime::Handle::current();
+        let (total, synced) = blocking(|| {
+            std::thread::scope(|scope| {
+                let writer = scope.spawn(|| runtime.block_on(write));
+                let total = runtime.block_on(fetch);
+                let synced = writer
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                Ok::<_, Error>((total?, synced?))
+            })
+        })?;
+        while let Ok(keys) = marks.try_recv() {
+            cursor.mark_synced(&keys).await?;
+        }
+
+        if cancelled {
+            return Err(Error::cancelled());
+        }
+        Ok((total, synced))
+    }
+
+    /// Upsert rows into MotherDuck, then mark them synced in PostgreSQL.
+    ///
+    /// Rows with a NULL primary key or a value over `sync.max_field_bytes` in a
+    /// column whose policy is `fail` are skipped, so they count as failed.
+    /// Soft-deleted rows are deleted from the target instead of upserted. Rows
+    /// read from a cursor are marked on the cursor's own connection (see
+    /// [`Marks`]). Returns the number of rows written or deleted.
+    async fn write_rows(
+        &self,
+        mapping: &TableMapping,
+        rows: &[HashMap<String, serde_json::Value>],
+        full_sync: bool,
+        total: Option<usize>,
+        marks: Marks<'_>,
+    ) -> Result<usize> {
+        let kept = without_rejected_rows(mapping, rows, &self.config.sync);
+        if kept.len() < rows.len() {
+            self.log_rejected_rows(mapping, rows);
+        }
+        let rows = kept;
+        let (live, deleted_keys) = split_soft_deleted(mapping, &rows);
+        let in_table_txn = !matches!(marks, Marks::Source) && self.table_transaction();
+
+        let upserted = if in_table_txn {
+            // Inside the table's transaction: a failed statement aborts it, so no retry
+            self.sink(|sink| sink.upsert(mapping, &live))?
+        } else {
+            self.retry("upsert", || async {
+                if self.config.sync.use_transactions {
+                    let start = Instant::now();
+                    self.sink(|sink| {
+                        sink.batch_upsert(
+                            mapping,
+                            &live,
+                            mapping.batch_size_or(self.config.sync.batch_size),
+                            self.config.sync.transaction_scope,
+                            &mut |written| {
+                                if let Some(total) = total {
+                                    self.report_progress(insert_progress(
+                                        &mapping.source_table,
+                                        written,
+                                        total,
+                                        start.elapsed(),
+                                    ));
+                                }
+                            },
+                        )
+                    })
+                } else {
+                    self.sink(|sink| sink.upsert(mapping, &live))
+                }
+            })
+            .await?
+        };
+
+        if !deleted_keys.is_empty() {
+            let deleted = if in_table_txn {
+                self.sink(|sink| sink.delete_rows(mapping, &deleted_keys))?
+            } else {
+                self.retry("delete", || async {
+                    self.sink(|sink| sink.delete_rows(mapping, &deleted_keys))
+                })
+                .await?
+            };
+            info!(
+                "Deleted {} soft-deleted rows from {}",
+                deleted, mapping.target_table
+            );
+        }
+        // Rows a row hash match left alone were still handled, so they get marked
+        // and aren't failures; they're reported apart from written rows
+        let unchanged = live.len().saturating_sub(upserted);
+        *self
+            .unchanged_rows
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) += unchanged;
+        let synced = live.len() + deleted_keys.len();
+
+        // Mark as synced in PostgreSQL (watermark tables track progress in MotherDuck instead)
+        if self.marks_synced(mapping, full_sync) && synced > 0 {
+            self.report_progress(SyncProgress {
+                table: mapping.source_table.clone(),
+                phase: SyncPhase::Marking,
+                records_processed: synced,
+                total_records: total,
+                percent: 75,
+                rows_per_second: None,
+                eta: None,
+            });
+
+            let keys: Vec<Vec<serde_json::Value>> = rows
+                .iter()
+                .filter_map(|r| primary_key_values(r, &mapping.primary_key))
+                .collect();
+
+            match marks {
+                Marks::Source => {
+                    self.source.mark_synced(mapping, &keys).await?;
+                }
+                Marks::Cursor(cursor) => {
+                    cursor.mark_synced(&keys).await?;
+                }
+                // The receiver is only dropped once the table has failed
+                Marks::Queued(queue) => {
+                    let _ = queue.send(keys);
+                }
+            }
+        }
+
+        Ok(synced)
+    }
+
+    /// Whether written rows get flagged synced in PostgreSQL.
+    fn marks_synced(&self, mapping: &TableMapping, full_sync: bool) -> bool {
+        self.config.sync.mark_synced
+            && mapping.sync_flag_column.is_some()
+            && mapping.watermark_column.is_none()
+            && !full_sync
+    }
+
+    /// Delete target rows that no longer exist in the source, if enabled.
+    ///
+    /// `live_keys` is the complete set of source keys when the caller already has
+    /// it; otherwise every live key is fetched from PostgreSQL, which scans the
+    /// whole source table.
+    async fn delete_missing_rows(
+        &self,
+        mapping: &TableMapping,
+        live_keys: Option<Vec<Vec<serde_json::Value>>>,
+    ) -> Result<usize> {
+        if !mapping.hard_delete {
+            return Ok(0);
+        }
+
+        let live_keys = match live_keys {
+            Some(keys) => keys,
+            None => {
+                self.retry("fetch keys", || self.source.fetch_primary_keys(mapping))
+                    .await?
+            }
+        };
+
+        let deleted = self
+            .retry("delete", || async {
+                self.sink(|sink| sink.delete_missing(mapping, &live_keys))
+            })
+            .await?;
+
+        if deleted > 0 {
+            info!(
+                "Deleted {} rows from {} no longer present in {}",
+                deleted, mapping.target_table, mapping.source_table
+            );
+        }
+        Ok(deleted)
+    }
+
+    /// Load the stored watermark for an incremental sync of a watermark table,
+    /// or the last key synced of a table that resumes by key.
+    fn stored_watermark(
+        &self,
+        mapping: &TableMapping,
+        full_sync: bool,
+    ) -> Result<Option<serde_json::Value>> {
+        if full_sync || (mapping.watermark_column.is_none() && !mapping.resumes_by_key()) {
+            return Ok(None);
+        }
+
+        let since = self.sink(|sink| sink.get_watermark(&mapping.target_table))?;
+        debug!("Stored watermark for {}: {:?}", mapping.target_table, since);
+        Ok(since)
+    }
+
+    /// Persist the watermark a sync reached (see [`Watermark`]).
+    fn store_watermark(
+        &self,
+        mapping: &TableMapping,
+        mode: SyncMode,
+        watermark: Option<&serde_json::Value>,
+        synced: usize,
+    ) -> Result<()> {
+        match watermark {
+            Some(w) => self.sink(|sink| {
+                sink.set_watermark(&mapping.target_table, w, synced, &mode.to_string())
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Report progress via callback.
+    fn report_progress(&self, progress: SyncProgress) {
+        if let Some(ref callback) = self.progress_callback {
+            callback(progress);
+        }
+    }
+
+    /// Get unsynced counts for all tables.
+    ///
+    /// Tables are counted concurrently, up to `postgres.pool_size` at a time, so
+    /// a status check takes about as long as the slowest count.
+    pub async fn get_unsynced_counts(&self) -> Result<HashMap<String, i64>> {
+        let mut pending = Vec::new();
+        for mapping in self.config.tables.iter().filter(|t| t.enabled) {
+            pending.push((mapping, self.stored_watermark(mapping, false)?));
+        }
+
+        futures_util::stream::iter(pending)
+            .map(|(mapping, since)| async move {
+                let count = self.source.unsynced_count(mapping, since.as_ref()).await?;
+                Ok::<_, Error>((mapping.source_table.clone(), count))
+            })
+            .buffer_unordered(self.config.postgres.pool_size.max(1) as usize)
+            .try_collect()
+            .await
+    }
+}
+
+/// Why a table can't be loaded through `sync.bulk_copy`, if it can't.
+///
+/// The CSV path writes source columns unchanged and in one go, so anything that
+/// rewrites rows, tracks a watermark or paces writes needs the row path.
+fn bulk_copy_blocker(mapping: &TableMapping, sync: &SyncBehaviorConfig) -> Option<&'static str> {
+    if !mapping.transforms.is_empty() {
+        Some("column transforms")
+    } else if !mapping.column_mappings.is_empty() {
+        Some("column mappings")
+    } else if !mapping.column_defaults.is_empty() {
+        Some("column defaults")
+    } else if mapping.watermark_column.is_some() {
+        Some("watermark column")
+    } else if mapping.soft_delete_column.is_some() {
+        Some("soft-delete column")
+    } else if !mapping.filter_params.is_empty() {
+        // COPY can't bind parameters
+        Some("filter parameters")
+    } else if sync.max_rows_per_second.is_some() {
+        Some("max_rows_per_second")
+    } else if sync.empty_string_as_null {
+        Some("empty_string_as_null")
+    } else if sync.stamp_columns {
+        Some("stamp_columns")
+    } else if mapping.row_hash {
+        Some("row_hash")
+    } else if sync.max_field_bytes.is_some() {
+        Some("max_field_bytes")
+    } else {
+        None
+    }
+}
+
+/// Append the stamp columns to the target's `columns`.
+fn add_stamp_columns(mapping: &TableMapping, columns: &mut Vec<IntrospectedColumn>) -> Result<()> {
+    let stamps = [(RUN_ID_COLUMN, "text"), (SYNCED_AT_COLUMN, "timestamptz")];
+    add_generated_columns(mapping, columns, &stamps, "sync.stamp_columns")
+}
+
+/// Append columns the sync fills itself, as `(name, PostgreSQL type)`, to the
+/// target's `columns`.
+///
+/// Fails if a source column, or the target name it is mapped to, already uses
+/// one of their names; `setting` names the option that adds them.
+fn add_generated_columns(
+    mapping: &TableMapping,
+    columns: &mut Vec<IntrospectedColumn>,
+    generated: &[(&str, &str)],
+    setting: &str,
+) -> Result<()> {
+    for (name, _) in generated {
+        if columns.iter().any(|c| {
+            c.name.eq_ignore_ascii_case(name)
+                || mapping.target_column(&c.name).eq_ignore_ascii_case(name)
+        }) {
+            return Err(Error::config(format!(
+                "Source table {} has a column named {}, which {} writes",
+                mapping.source_table, name, setting
+            )));
+        }
+    }
+    columns.extend(generated.iter().map(|(name, pg_type)| IntrospectedColumn {
+        name: name.to_string(),
+        pg_type: pg_type.to_string(),
+        nullable: true,
+        default: None,
+        is_primary_key: false,
+    }));
+    Ok(())
+}
+
+/// A fresh run id and the current time, as written to the stamp columns.
+fn new_run_stamp() -> (String, String) {
+    (
+        uuid::Uuid::new_v4().to_string(),
+        chrono::Utc::now().to_rfc3339(),
+    )
+}
+
+/// Set the stamp columns of every row to `run_id` and `synced_at`.
+fn stamp_rows(rows: &mut [HashMap<String, serde_json::Value>], run_id: &str, synced_at: &str) {
+    for row in rows {
+        row.insert(RUN_ID_COLUMN.to_string(), run_id.into());
+        row.insert(SYNCED_AT_COLUMN.to_string(), synced_at.into());
+    }
+}
+
+/// Set the row hash column of every row to a SHA-256 of its other values.
+///
+/// Values are serialized as JSON with sorted column names, so the hash only
+/// changes when the row's content does.
+fn hash_rows(rows: &mut [HashMap<String, serde_json::Value>]) {
+    use sha2::{Digest, Sha256};
+
+    for row in rows {
+        row.remove(ROW_HASH_COLUMN);
+        let sorted: BTreeMap<&String, &serde_json::Value> = row.iter().collect();
+        let json = serde_json::to_vec(&sorted).unwrap_or_default();
+        let hash: String = Sha256::digest(&json)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        row.insert(ROW_HASH_COLUMN.to_string(), hash.into());
+    }
+}
+
+/// Replace empty string values with NULL, except in primary key columns.
+fn empty_strings_to_null(mapping: &TableMapping, rows: &mut [HashMap<String, serde_json::Value>]) {
+    for row in rows {
+        for (column, value) in row.iter_mut() {
+            if value.as_str() == Some("") && !mapping.primary_key.contains(column) {
+                *value = serde_json::Value::Null;
+            }
+        }
+    }
+}
+
+/// Whether `row` lacks a value for a primary key column.
+///
+/// Such a row would be upserted with a NULL key, which never conflicts with
+/// an existing row, and could never be marked synced.
+fn null_key(mapping: &TableMapping, row: &HashMap<String, serde_json::Value>) -> bool {
+    primary_key_values(row, &mapping.primary_key).is_none()
+}
+
+/// Log a row [`null_key`] rejects, with an excerpt of its fields.
+fn log_null_key(mapping: &TableMapping, row: &HashMap<String, serde_json::Value>) {
+    let fields: BTreeMap<&String, &serde_json::Value> = row.iter().collect();
+    let mut fields = serde_json::to_string(&fields).unwrap_or_default();
+    if let Some((end, _)) = fields.char_indices().nth(NULL_KEY_LOG_CHARS) {
+        fields.truncate(end);
+        fields.push_str("...");
+    }
+    warn!(
+        "Skipping row of {} with a NULL primary key ({}): {}",
+        mapping.source_table,
+        mapping.primary_key.join(", "),
+        fields
+    );
+}
+
+/// Longest excerpt of a skipped row [`log_null_key`] logs.
+const NULL_KEY_LOG_CHARS: usize = 200;
+
+/// The column of `row` holding a value over `sync.max_field_bytes` whose
+/// policy is `fail`, if any. Other oversized values were already cut down by
+/// [`limit_field_sizes`].
+fn oversized_column<'a>(
+    mapping: &TableMapping,
+    row: &'a HashMap<String, serde_json::Value>,
+    sync: &SyncBehaviorConfig,
+) -> Option<&'a String> {
+    let max_bytes = sync.max_field_bytes?;
+    row.iter()
+        .find(|(column, value)| {
+            mapping.oversized_policy(column, sync.oversized_fields) == OversizedFieldPolicy::Fail
+                && field_bytes(value) > max_bytes
+        })
+        .map(|(column, _)| column)
+}
+
+/// Whether `row` can't be written: it has a NULL key ([`null_key`]) or an
+/// oversized value that fails it ([`oversized_column`]).
+fn rejected_row(
+    mapping: &TableMapping,
+    row: &HashMap<String, serde_json::Value>,
+    sync: &SyncBehaviorConfig,
+) -> bool {
+    null_key(mapping, row) || oversized_column(mapping, row, sync).is_some()
+}
+
+/// `rows` without those [`rejected_row`] rejects. Borrows `rows` unchanged
+/// when every row can be written.
+fn without_rejected_rows<'a>(
+    mapping: &TableMapping,
+    rows: &'a [HashMap<String, serde_json::Value>],
+    sync: &SyncBehaviorConfig,
+) -> Cow<'a, [HashMap<String, serde_json::Value>]> {
+    let rejected: Vec<bool> = rows
+        .iter()
+        .map(|r| rejected_row(mapping, r, sync))
+        .collect();
+    if !rejected.contains(&true) {
+        return Cow::Borrowed(rows);
+    }
+    Cow::Owned(
+        rows.iter()
+            .zip(rejected)
+            .filter(|(_, rejected)| !rejected)
+            .map(|(r, _)| r.clone())
+            .collect(),
+    )
+}
+
+/// Size of a text or JSON value as [`limit_field_sizes`] measures it: the
+/// bytes of a string, or of an object or array serialized. Other values don't
+/// count.
+fn field_bytes(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::String(s) => s.len(),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+            serde_json::to_string(value).map_or(0, |json| json.len())
+        }
+        _ => 0,
+    }
+}
+
+/// Ends a string [`limit_field_sizes`] truncated.
+const TRUNCATED_MARKER: &str = "[_truncated]";
+
+/// Values over `sync.max_field_bytes` by column and what became of them, logged
+/// once per table by [`SyncClient::log_oversized_values`].
+type OversizedValues = BTreeMap<(String, &'static str), usize>;
+
+/// Truncate or null values over `max_bytes` as their column's policy says,
+/// counting each in `oversized`. Values of `fail` columns are left for
+/// [`oversized_column`].
+fn limit_field_sizes(
+    mapping: &TableMapping,
+    rows: &mut [HashMap<String, serde_json::Value>],
+    max_bytes: usize,
+    default: OversizedFieldPolicy,
+    oversized: &mut OversizedValues,
+) {
+    for row in rows {
+        for (column, value) in row.iter_mut() {
+            let bytes = field_bytes(value);
+            if bytes <= max_bytes {
+                continue;
+            }
+            match mapping.oversized_policy(column, default) {
+                OversizedFieldPolicy::Fail => continue,
+                OversizedFieldPolicy::Null => *value = serde_json::Value::Null,
+                OversizedFieldPolicy::Truncate => match value {
+                    serde_json::Value::String(s) => {
+                        let mut end = max_bytes.saturating_sub(TRUNCATED_MARKER.len());
+                        while !s.is_char_boundary(end) {
+                            end -= 1;
+                        }
+                        s.truncate(end);
+                        s.push_str(TRUNCATED_MARKER);
+                    }
+                    _ => *value = serde_json::json!({"_truncated": true, "bytes": bytes}),
+                },
+            }
+            let outcome = if value.is_null() {
+                "written as NULL"
+            } else {
+                "truncated"
+            };
+            *oversized.entry((column.clone(), outcome)).or_default() += 1;
+        }
+    }
+}
+
+/// Rows to upsert and primary keys to delete, as split by [`split_soft_deleted`].
+type SoftDeleteSplit<'a> = (
+    Cow<'a, [HashMap<String, serde_json::Value>]>,
+    Vec<Vec<serde_json::Value>>,
+);
+
+/// Split fetched rows into those to upsert and the primary keys of the
+/// soft-deleted ones, which are deleted from the target instead.
+///
+/// Borrows `rows` unchanged when none are soft-deleted.
+fn split_soft_deleted<'a>(
+    mapping: &TableMapping,
+    rows: &'a [HashMap<String, serde_json::Value>],
+) -> SoftDeleteSplit<'a> {
+    if !rows.iter().any(|r| mapping.is_soft_deleted(r)) {
+        return (Cow::Borrowed(rows), Vec::new());
+    }
+
+    let (deleted, live): (Vec<_>, Vec<_>) = rows.iter().partition(|r| mapping.is_soft_deleted(r));
+    let keys = deleted
+        .into_iter()
+        .filter_map(|r| primary_key_values(r, &mapping.primary_key))
+        .collect();
+    (Cow::Owned(live.into_iter().cloned().collect()), keys)
+}
+
+/// Temporary CSV file staging a bulk copy into `target_table`.
+fn bulk_copy_path(target_table: &str) -> PathBuf {
+    let name: String = target_table
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    std::env::temp_dir().join(format!("supasync-{}-{}.csv", std::process::id(), name))
+}
+
+/// Extract a row's primary key tuple in key column order.
+///
+/// Returns `None` if any key column is missing or NULL.
+fn primary_key_values(
+    row: &HashMap<String, serde_json::Value>,
+    primary_key: &[String],
+) -> Option<Vec<serde_json::Value>> {
+    primary_key
+        .iter()
+        .map(|col| row.get(col).filter(|v| !v.is_null()).cloned())
+        .collect()
+}
+
+/// The watermark a sync stores for a watermark table.
+///
+/// Only rows that get written count. The next run fetches rows past the stored
+/// watermark, so it stays below every row [`rejected_row`] skipped.
+#[derive(Debug, Default)]
+struct Watermark {
+    /// Highest watermark of a written row below `held_below`
+    highest: Option<serde_json::Value>,
+    /// Lowest watermark of a skipped row
+    held_below: Option<serde_json::Value>,
+}
+
+impl Watermark {
+    /// The watermark to store, if any row advanced it.
+    fn value(&self) -> Option<&serde_json::Value> {
+        self.highest.as_ref()
+    }
+}
+
+/// Raise `watermark` to the highest watermark column value in `rows`, holding it
+/// below rows that are skipped.
+///
+/// Numbers compare numerically and strings lexically, which orders the ISO-8601
+/// timestamps produced by `row_to_json`. A skipped row at or below the current
+/// watermark clears it, leaving the stored one in place. Does nothing for tables
+/// without a watermark column.
+fn advance_watermark(
+    watermark: &mut Watermark,
+    mapping: &TableMapping,
+    rows: &[HashMap<String, serde_json::Value>],
+    sync: &SyncBehaviorConfig,
+) {
+    let Some(ref col) = mapping.watermark_column else {
+        return;
+    };
+
+    for row in rows {
+        let Some(value) = row.get(col).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        let below_held = watermark
+            .held_below
+            .as_ref()
+            .is_none_or(|held| watermark_gt(held, value));
+        if rejected_row(mapping, row, sync) {
+            if below_held {
+                if watermark
+                    .highest
+                    .as_ref()
+                    .is_some_and(|highest| !watermark_gt(value, highest))
+                {
+                    watermark.highest = None;
+                }
+                watermark.held_below = Some(value.clone());
+            }
+        } else if below_held
+            && watermark
+                .highest
+                .as_ref()
+                .is_none_or(|highest| watermark_gt(value, highest))
+        {
+            watermark.highest = Some(value.clone());
+        }
+    }
+}
+
+/// Compare two watermark values of the same JSON type.
+fn watermark_gt(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    match (a, b) {
+        (serde_json::Value::Number(x), serde_json::Value::Number(y)) => {
+            match (x.as_i64(), y.as_i64()) {
+                (Some(x), Some(y)) => x > y,
+                _ => x.as_f64().unwrap_or(f64::MIN) > y.as_f64().unwrap_or(f64::MIN),
+            }
+        }
+        (serde_json::Value::String(x), serde_json::Value::String(y)) => x > y,
+        _ => false,
+    }
+}
+
+/// Connect to PostgreSQL, retrying transient failures.
+async fn connect_postgres(config: &SyncConfig, metrics: &Arc<Metrics>) -> Result<PostgresClient> {
+    let client = retry_with_backoff(
+        &config.retry,
+        metrics,
+        "PostgreSQL connect",
+        || PostgresClient::connect(config.postgres.clone()),
+        |_| {},
+    )
+    .await?;
+    Ok(client.with_metrics(metrics.clone()))
+}
+
+/// Connect to MotherDuck, retrying transient failures.
+async fn connect_motherduck(
+    config: &SyncConfig,
+    metrics: &Arc<Metrics>,
+) -> Result<MotherDuckClient> {
+    let client = retry_with_backoff(
+        &config.retry,
+        metrics,
+        "MotherDuck connect",
+        || async { blocking(|| MotherDuckClient::connect(config.motherduck.clone())) },
+        |_| {},
+    )
+    .await?;
+    Ok(client
+        .with_metrics(metrics.clone())
+        .with_rate_limit(config.sync.max_rows_per_second))
+}
+
+/// Where [`SyncClient::write_rows`] marks written rows synced.
+#[derive(Clone, Copy)]
+enum Marks<'a> {
+    /// Through the source, committed right away
+    Source,
+    /// On the cursor the rows were read from, committed when it closes
+    Cursor(&'a dyn SourceCursor),
+    /// Queued for the cursor the rows were read from, while it fetches more
+    Queued(&'a tokio::sync::mpsc::UnboundedSender<Vec<Vec<serde_json::Value>>>),
+}
+
+/// Whether the current tokio runtime is multi-threaded, so a worker can block.
+fn multi_threaded_runtime() -> bool {
+    tokio::runtime::Handle::try_current()
+        .is_ok_and(|handle| handle.runtime_flavor() == RuntimeFlavor::MultiThread)
+}
+
+/// Run blocking DuckDB work from async code.
+///
+/// DuckDB calls are synchronous. On a multi-threaded runtime the current worker
+/// hands its other tasks to the rest of the pool while `f` runs
+/// (`block_in_place`), so they keep making progress; on a current-thread runtime,
+/// where that isn't possible, `f` runs inline.
+fn blocking<T>(f: impl FnOnce() -> T) -> T {
+    if multi_threaded_runtime() {
+        tokio::task::block_in_place(f)
+    } else {
+        f()
+    }
+}
+
+/// A row's primary key as the JSON object [`PostgresClient::fetch_page`] resumes after.
+fn keyset_position(
+    mapping: &TableMapping,
+    row: &HashMap<String, serde_json::Value>,
+) -> Option<serde_json::Value> {
+    let key = mapping
+        .primary_key
+        .iter()
+        .map(|col| Some((col.clone(), row.get(col)?.clone())))
+        .collect::<Option<serde_json::Map<_, _>>>()?;
+    Some(serde_json::Value::Object(key))
+}
+
+/// Rows per second over `elapsed`, once any time has passed.
+fn throughput(rows: usize, elapsed: Duration) -> Option<f64> {
+    let secs = elapsed.as_secs_f64();
+    (rows > 0 && secs > 0.0).then(|| rows as f64 / secs)
+}
+
+/// Progress after `written` of `total` rows were upserted in `elapsed`.
+///
+/// The insert phase covers 25-75%; the ETA assumes the observed rate holds.
+fn insert_progress(table: &str, written: usize, total: usize, elapsed: Duration) -> SyncProgress {
+    let rows_per_second = throughput(written, elapsed);
+    let remaining = total.saturating_sub(written);
+    SyncProgress {
+        table: table.to_string(),
+        phase: SyncPhase::Inserting,
+        records_processed: written,
+        total_records: Some(total),
+        percent: (25 + written.min(total) * 50 / total.max(1)) as u8,
+        rows_per_second,
+        eta: rows_per_second.map(|rate| Duration::from_secs_f64(remaining as f64 / rate)),
+    }
+}
+
+/// Look up tables by source or target name, keeping config order.
+fn select_tables<'a>(tables: &'a [TableMapping], names: &[&str]) -> Result<Vec<&'a TableMapping>> {
+    let matches = |t: &TableMapping, name: &str| t.source_table == name || t.target_table == name;
+
+    for name in names {
+        match tables.iter().find(|t| matches(t, name)) {
+            None => return Err(Error::config(format!("Unknown table: {}", name))),
+            Some(t) if !t.enabled => {
+                return Err(Error::config(format!("Table {} is disabled", name)));
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(tables
+        .iter()
+        .filter(|t| names.iter().any(|name| matches(t, name)))
+        .collect())
+}
+
+/// Create exponential backoff from config.
+pub fn create_backoff(config: &RetryConfig) -> ExponentialBackoff {
+    ExponentialBackoffBuilder::new()
+        .with_initial_interval(config.initial_backoff())
+        .with_max_interval(config.max_backoff())
+        .with_multiplier(config.multiplier)
+        .with_randomization_factor(if config.jitter { 0.5 } else { 0.0 })
+        .with_max_elapsed_time(Some(Duration::from_secs(300)))
+        .build()
+}
+
+/// Run an operation, retrying retryable errors with exponential backoff.
+///
+/// Non-retryable errors are returned immediately. Once `max_retries` is reached
+/// (or the backoff gives up) the last error is wrapped in `Error::RetryExhausted`.
+/// `on_retry` sees every error that is retried.
+async fn retry_with_backoff<T, F, Fut>(
+    config: &RetryConfig,
+    metrics: &Metrics,
+    operation: &str,
+    mut op: F,
+    mut on_retry: impl FnMut(&Error),
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut backoff = create_backoff(config);
+    let mut attempts = 0;
+
+    loop {
+        attempts += 1;
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) => {
+                back_off(
+                    config,
+                    &mut backoff,
+                    metrics,
+                    operation,
+                    attempts,
+                    e,
+                    &mut on_retry,
+                )
+                .await?
+            }
+        }
+    }
+}
+
+/// Wait before the next try of `operation`, whose `attempts`-th try failed
+/// with `err`, or return the error to give up with.
+///
+/// The step between tries of [`retry_with_backoff`], for callers whose
+/// operation needs state it can't capture in an `FnMut`, like `&mut` access.
+async fn back_off(
+    config: &RetryConfig,
+    backoff: &mut ExponentialBackoff,
+    metrics: &Metrics,
+    operation: &str,
+    attempts: u32,
+    err: Error,
+    on_retry: &mut impl FnMut(&Error),
+) -> Result<()> {
+    if !err.is_retryable() {
+        return Err(err);
+    }
+
+    let delay = if attempts <= config.max_retries {
+        backoff.next_backoff()
+    } else {
+        None
+    };
+
+    match delay {
+        Some(delay) => {
+            warn!(
+                "{} failed (attempt {}), retrying in {:?}: {}",
+                operation, attempts, delay, err
+            );
+            metrics.record_retry();
+            on_retry(&err);
+            tokio::time::sleep(delay).await;
+            Ok(())
+        }
+        None => Err(Error::RetryExhausted {
+            attempts,
+            message: format!("{} failed: {}", operation, err),
+            last_error: Some(Box::new(err)),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_mode_display() {
+        assert_eq!(SyncMode::Incremental.to_string(), "incremental");
+        assert_eq!(SyncMode::Full.to_string(), "full");
+        assert_eq!(SyncMode::DryRun.to_string(), "dry-run");
+    }
+
+    #[test]
+    fn test_health_report() {
+        let healthy = BackendHealth {
+            ok: true,
+            latency_ms: 12,
+            error: None,
+        };
+        let report = HealthReport::new(healthy.clone(), healthy.clone());
+        assert!(report.ok);
+        assert_eq!(
+            report.to_string(),
+            "PostgreSQL ok (12ms), MotherDuck ok (12ms)"
+        );
+
+        let down = BackendHealth::from_check(Err(Error::schema("boom")), Instant::now());
+        assert!(!down.ok);
+        assert_eq!(down.error.as_deref(), Some("Schema error: boom"));
+
+        let report = HealthReport::new(healthy, down);
+        assert!(!report.ok);
+        assert!(report.postgres.ok);
+        assert!(
+            report
+                .to_string()
+                .ends_with("MotherDuck failed (0ms): Schema error: boom")
+        );
+    }
+
+    fn fast_retry(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_backoff_ms: 1,
+            max_backoff_ms: 1,
+            jitter: false,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_transient_errors() {
+        let metrics = Metrics::new();
+        let mut calls = 0;
+
+        let mut retried = Vec::new();
+
+        let result = retry_with_backoff(
+            &fast_retry(3),
+            &metrics,
+            "test",
+            || {
+                calls += 1;
+                let attempt = calls;
+                async move {
+                    if attempt < 3 {
+                        Err(Error::Io(std::io::Error::other("connection reset")))
+                    } else {
+                        Ok(attempt)
+                    }
+                }
+            },
+            |e| retried.push(e.to_string()),
+        )
+        .await;
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(metrics.snapshot().retries, 2);
+        assert_eq!(retried.len(), 2);
+        assert!(retried[1].contains("connection reset"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_exhausted() {
+        let metrics = Metrics::new();
+
+        let result: Result<()> = retry_with_backoff(
+            &fast_retry(2),
+            &metrics,
+            "test",
+            || async { Err(Error::Io(std::io::Error::other("connection reset"))) },
+            |_| {},
+        )
+        .await;
+
+        match result {
+            Err(Error::RetryExhausted { attempts, .. }) => assert_eq!(attempts, 3),
+            other => panic!("expected RetryExhausted, got {:?}", other),
+        }
+        assert_eq!(metrics.snapshot().retries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_non_retryable_errors() {
+        let metrics = Metrics::new();
+
+        let result: Result<()> = retry_with_backoff(
+            &fast_retry(3),
+            &metrics,
+            "test",
+            || async { Err(Error::validation("bad input")) },
+            |_| {},
+        )
+        .await;
+
+        assert!(matches!(result, Err(Error::Validation(_))));
+        assert_eq!(metrics.snapshot().retries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_new_retries_connect() {
+        // Nothing listens on port 1, so every attempt is refused at once
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.retry = fast_retry(2);
+        let metrics = Arc::new(Metrics::new());
+
+        match SyncClient::new_with_metrics(config, metrics.clone()).await {
+            Err(Error::RetryExhausted { attempts, .. }) => assert_eq!(attempts, 3),
+            Err(other) => panic!("expected RetryExhausted, got {:?}", other),
+            Ok(_) => panic!("expected RetryExhausted, got a client"),
+        }
+        assert_eq!(metrics.snapshot().retries, 2);
+    }
+
+    #[test]
+    fn test_primary_key_values_composite() {
+        let pk = vec!["tenant_id".to_string(), "id".to_string()];
+
+        let mut row = HashMap::new();
+        row.insert("id".to_string(), serde_json::json!(7));
+        row.insert("tenant_id".to_string(), serde_json::json!("acme"));
+        row.insert("name".to_string(), serde_json::json!("x"));
+        assert_eq!(
+            primary_key_values(&row, &pk),
+            Some(vec![serde_json::json!("acme"), serde_json::json!(7)])
+        );
+
+        row.insert("tenant_id".to_string(), serde_json::Value::Null);
+        assert_eq!(primary_key_values(&row, &pk), None);
+    }
+
+    #[test]
+    fn test_split_soft_deleted() {
+        let mapping = TableMapping::builder()
+            .source_table("posts")
+            .primary_key(["tenant_id", "id"])
+            .soft_delete_column("deleted_at")
+            .build()
+            .unwrap();
+        let row = |id: i64, deleted_at: serde_json::Value| {
+            HashMap::from([
+                ("tenant_id".to_string(), serde_json::json!("acme")),
+                ("id".to_string(), serde_json::json!(id)),
+                ("deleted_at".to_string(), deleted_at),
+            ])
+        };
+
+        let rows = vec![
+            row(1, serde_json::Value::Null),
+            row(2, serde_json::json!("2024-05-01T00:00:00Z")),
+            row(3, serde_json::Value::Null),
+        ];
+        let (live, deleted) = split_soft_deleted(&mapping, &rows);
+        assert_eq!(live.len(), 2);
+        assert!(live.iter().all(|r| r["deleted_at"].is_null()));
+        assert_eq!(
+            deleted,
+            vec![vec![serde_json::json!("acme"), serde_json::json!(2)]]
+        );
+
+        // Nothing soft-deleted: the rows pass through without a copy
+        let (live, deleted) = split_soft_deleted(&mapping, &rows[..1]);
+        assert!(matches!(live, Cow::Borrowed(_)));
+        assert!(deleted.is_empty());
+    }
+
+    #[test]
+    fn test_insert_progress() {
+        let progress = insert_progress("users", 250, 1000, Duration::from_secs(1));
+        assert_eq!(progress.phase, SyncPhase::Inserting);
+        assert_eq!(progress.percent, 37);
+        assert_eq!(progress.rows_per_second, Some(250.0));
+        assert_eq!(progress.eta, Some(Duration::from_secs(3)));
+
+        let done = insert_progress("users", 1000, 1000, Duration::from_secs(2));
+        assert_eq!(done.percent, 75);
+        assert_eq!(done.eta, Some(Duration::ZERO));
+
+        // Nothing observed yet, so no rate to extrapolate from
+        let start = insert_progress("users", 0, 1000, Duration::ZERO);
+        assert_eq!(start.rows_per_second, None);
+        assert_eq!(start.eta, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_blocking_on_any_runtime() {
+        assert_eq!(blocking(|| 1), 1);
+        // No block_in_place on a current-thread runtime; runs inline instead
+        let inline = tokio::task::spawn_blocking(|| {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async { blocking(|| 2) })
+        });
+        assert_eq!(inline.await.unwrap(), 2);
+    }
+
+    #[test]
+    fn test_select_tables() {
+        let table = |source: &str, target: &str, enabled: bool| {
+            TableMapping::builder()
+                .source_table(source)
+                .target_table(target)
+                .primary_key_column("id")
+                .enabled(enabled)
+                .build()
+                .unwrap()
+        };
+        let tables = vec![
+            table("staging_users", "full_users", true),
+            table("analytics_daily_stats", "daily_stats", true),
+            table("legacy", "legacy", false),
+        ];
+
+        let selected = select_tables(&tables, &["daily_stats", "staging_users"]).unwrap();
+        let sources: Vec<&str> = selected.iter().map(|t| t.source_table.as_str()).collect();
+        assert_eq!(sources, vec!["staging_users", "analytics_daily_stats"]);
+
+        assert!(select_tables(&tables, &["missing"]).is_err());
+        assert!(select_tables(&tables, &["legacy"]).is_err());
+    }
+
+    #[test]
+    fn test_empty_strings_to_null() {
+        let mapping = TableMapping::builder()
+            .source_table("tags")
+            .primary_key_column("code")
+            .build()
+            .unwrap();
+        let mut rows = vec![HashMap::from([
+            ("code".to_string(), serde_json::json!("")),
+            ("label".to_string(), serde_json::json!("")),
+            ("note".to_string(), serde_json::json!(" ")),
+            ("parent".to_string(), serde_json::Value::Null),
+        ])];
+
+        empty_strings_to_null(&mapping, &mut rows);
+        assert_eq!(rows[0]["code"], serde_json::json!(""));
+        assert!(rows[0]["label"].is_null());
+        assert_eq!(rows[0]["note"], serde_json::json!(" "));
+        assert!(rows[0]["parent"].is_null());
+    }
+
+    #[test]
+    fn test_hash_rows() {
+        let row = |name: &str| {
+            HashMap::from([
+                ("id".to_string(), serde_json::json!(1)),
+                ("name".to_string(), serde_json::json!(name)),
+                ("tags".to_string(), serde_json::json!(["a", "b"])),
+            ])
+        };
+        let hashes = |rows: &[HashMap<String, serde_json::Value>]| -> Vec<String> {
+            rows.iter()
+                .map(|r| r[ROW_HASH_COLUMN].as_str().unwrap().to_string())
+                .collect()
+        };
+        let mut rows = vec![row("ann"), row("ann"), row("bob")];
+        hash_rows(&mut rows);
+        let before = hashes(&rows);
+        assert_eq!(before[0].len(), 64);
+        assert_eq!(before[0], before[1]);
+        assert_ne!(before[0], before[2]);
+
+        // Rehashing ignores the stored hash
+        hash_rows(&mut rows[..1]);
+        assert_eq!(hashes(&rows)[0], before[0]);
+    }
+
+    #[test]
+    fn test_stamp_columns() {
+        let mapping = TableMapping::builder()
+            .source_table("orders")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+        let column = |name: &str| IntrospectedColumn {
+            name: name.to_string(),
+            pg_type: "int8".to_string(),
+            nullable: false,
+            default: None,
+            is_primary_key: name == "id",
+        };
+
+        let mut columns = vec![column("id")];
+        add_stamp_columns(&mapping, &mut columns).unwrap();
+        let added: Vec<(&str, &str)> = columns[1..]
+            .iter()
+            .map(|c| (c.name.as_str(), c.pg_type.as_str()))
+            .collect();
+        assert_eq!(
+            added,
+            vec![(RUN_ID_COLUMN, "text"), (SYNCED_AT_COLUMN, "timestamptz")]
+        );
+        assert!(columns[1..].iter().all(|c| c.nullable));
+
+        let mut colliding = vec![column("id"), column("_Synced_At")];
+        assert!(add_stamp_columns(&mapping, &mut colliding).is_err());
+
+        let renamed = TableMapping::builder()
+            .source_table("orders")
+            .primary_key_column("id")
+            .map_column("ref", RUN_ID_COLUMN)
+            .build()
+            .unwrap();
+        let mut mapped = vec![column("id"), column("ref")];
+        assert!(add_stamp_columns(&renamed, &mut mapped).is_err());
+
+        let mut rows = vec![HashMap::from([("id".to_string(), serde_json::json!(1))])];
+        stamp_rows(&mut rows, "run-1", "2026-01-01T00:00:00+00:00");
+        assert_eq!(rows[0][RUN_ID_COLUMN], serde_json::json!("run-1"));
+        assert_eq!(
+            rows[0][SYNCED_AT_COLUMN],
+            serde_json::json!("2026-01-01T00:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn test_bulk_copy_blocker() {
+        let sync = SyncBehaviorConfig::default();
+        let plain = TableMapping::builder()
+            .source_table("events")
+            .primary_key_column("id")
+            .build()
+            .unwrap();
+        assert_eq!(bulk_copy_blocker(&plain, &sync), None);
+
+        let hashed = TableMapping::builder()
+            .source_table("users")
+            .primary_key_column("id")
+            .transform("email", crate::config::ColumnTransform::Sha256)
+            .build()
+            .unwrap();
+        assert_eq!(bulk_copy_blocker(&hashed, &sync), Some("column transforms"));
+
+        let throttled = SyncBehaviorConfig {
+            max_rows_per_second: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(
+            bulk_copy_blocker(&plain, &throttled),
+            Some("max_rows_per_second")
+        );
+
+        let blank_as_null = SyncBehaviorConfig {
+            empty_string_as_null: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            bulk_copy_blocker(&plain, &blank_as_null),
+            Some("empty_string_as_null")
+        );
+
+        let stamped = SyncBehaviorConfig {
+            stamp_columns: true,
+            ..Default::default()
+        };
+        assert_eq!(bulk_copy_blocker(&plain, &stamped), Some("stamp_columns"));
+
+        let row_hashed = TableMapping {
+            row_hash: true,
+            ..plain.clone()
+        };
+        assert_eq!(bulk_copy_blocker(&row_hashed, &sync), Some("row_hash"));
+
+        let path = bulk_copy_path("main.daily stats");
+        assert!(path.ends_with(format!(
+            "supasync-{}-main_daily_stats.csv",
+            std::process::id()
+        )));
+    }
+
+    #[test]
+    fn test_advance_watermark() {
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key_column("id")
+            .watermark_column("updated_at")
+            .build()
+            .unwrap();
+
+        let row = |id: Option<i64>, ts: &str| {
+            HashMap::from([
+                ("id".to_string(), serde_json::json!(id)),
+                ("updated_at".to_string(), serde_json::json!(ts)),
+            ])
+        };
+        let rows = [
+            row(Some(1), "2024-01-02T00:00:00+00:00"),
+            row(Some(2), "2024-01-03T00:00:00.500+00:00"),
+            row(Some(3), "2024-01-01T00:00:00+00:00"),
+        ];
+        let sync = SyncBehaviorConfig::default();
+
+        let mut watermark = Watermark::default();
+        advance_watermark(&mut watermark, &mapping, &rows, &sync);
+        assert_eq!(
+            watermark.value(),
+            Some(&serde_json::json!("2024-01-03T00:00:00.500+00:00"))
+        );
+
+        // Never moves backwards
+        advance_watermark(&mut watermark, &mapping, &rows[2..], &sync);
+        assert_eq!(
+            watermark.value(),
+            Some(&serde_json::json!("2024-01-03T00:00:00.500+00:00"))
+        );
+
+        // Held below a skipped row, so the next run fetches it again
+        let mut watermark = Watermark::default();
+        let rows = [
+            row(Some(1), "2024-01-01T00:00:00+00:00"),
+            row(None, "2024-01-02T00:00:00+00:00"),
+            row(Some(3), "2024-01-03T00:00:00+00:00"),
+        ];
+        advance_watermark(&mut watermark, &mapping, &rows, &sync);
+        assert_eq!(
+            watermark.value(),
+            Some(&serde_json::json!("2024-01-01T00:00:00+00:00"))
+        );
+
+        // A skipped row below the watermark leaves the stored one in place
+        advance_watermark(
+            &mut watermark,
+            &mapping,
+            &[row(None, "2024-01-01T00:00:00+00:00")],
+            &sync,
+        );
+        assert_eq!(watermark.value(), None);
+        advance_watermark(&mut watermark, &mapping, &rows[2..], &sync);
+        assert_eq!(watermark.value(), None);
+
+        assert!(watermark_gt(
+            &serde_json::json!(9_007_199_254_740_993_i64),
+            &serde_json::json!(9_007_199_254_740_992_i64)
+        ));
+    }
+
+    #[test]
+    fn test_sync_result_totals() {
+        let mut tables = HashMap::new();
+        tables.insert(
+            "table1".to_string(),
+            TableSyncResult {
+                source_table: "table1".to_string(),
+                target_table: "table1".to_string(),
+                success: true,
+                records_synced: 100,
+                records_failed: 5,
+                records_unchanged: 0,
+                duration_ms: 1000,
+                error: None,
+                error_code: None,
+                retryable: false,
+                dry_run: false,
+                sql: Vec::new(),
+                attempts: 1,
+                last_retry_reason: None,
+            },
+        );
+        tables.insert(
+            "table2".to_string(),
+            TableSyncResult {
+                source_table: "table2".to_string(),
+                target_table: "table2".to_string(),
+                success: true,
+                records_synced: 50,
+                records_failed: 0,
+                records_unchanged: 0,
+                duration_ms: 500,
+                error: None,
+                error_code: None,
+                retryable: false,
+                dry_run: false,
+                sql: Vec::new(),
+                attempts: 1,
+                last_retry_reason: None,
+            },
+        );
+
+        let result = SyncResult {
+            success: true,
+            mode: "incremental".to_string(),
+            tables,
+            duration_ms: 1500,
+            completed_at: "2024-01-01T00:00:00Z".to_string(),
+            error: None,
+        };
+
+        assert_eq!(result.total_records(), 150);
+        assert_eq!(result.total_failed(), 5);
+        assert!(result.all_tables_success());
+        assert_eq!(result.throughput_records_per_sec(), 100.0);
+    }
+
+    #[test]
+    fn test_sync_result_report() {
+        let table = |name: &str, duration_ms: u64, error: Option<&str>| TableSyncResult {
+            source_table: name.to_string(),
+            target_table: format!("md_{}", name),
+            success: error.is_none(),
+            records_synced: 10,
+            records_failed: 0,
+            records_unchanged: 0,
+            duration_ms,
+            error: error.map(str::to_string),
+            error_code: None,
+            retryable: false,
+            dry_run: false,
+            sql: Vec::new(),
+            attempts: 1,
+            last_retry_reason: None,
+        };
+        let tables = [
+            table("users", 300, None),
+            table(
+                "orders",
+                900,
+                Some("relation \"orders\" does not exist, skipped"),
+            ),
+            table("events", 100, Some("timeout")),
+        ];
+        let result = SyncResult {
+            success: false,
+            mode: "full".to_string(),
+            tables: tables
+                .into_iter()
+                .map(|t| (t.source_table.clone(), t))
+                .collect(),
+            duration_ms: 0,
+            completed_at: "2024-01-01T00:00:00Z".to_string(),
+            error: None,
+        };
+
+        assert_eq!(result.slowest_table().unwrap().source_table, "orders");
+        let failed: Vec<&str> = result
+            .failed_tables()
+            .iter()
+            .map(|t| t.source_table.as_str())
+            .collect();
+        assert_eq!(failed, ["events", "orders"]);
+        assert_eq!(result.throughput_records_per_sec(), 0.0);
+        assert_eq!(
+            result.to_csv_summary(),
+            "completed_at,mode,source_table,target_table,success,records_synced,records_failed,duration_ms,error\n\
+             2024-01-01T00:00:00Z,full,events,md_events,false,10,0,100,timeout\n\
+             2024-01-01T00:00:00Z,full,orders,md_orders,false,10,0,900,\"relation \"\"orders\"\" does not exist, skipped\"\n\
+             2024-01-01T00:00:00Z,full,users,md_users,true,10,0,300,\n"
+        );
+    }
+
+    #[test]
+    fn test_sync_result_delta() {
+        let table =
+            |name: &str, records_synced: usize, duration_ms: u64, success: bool| TableSyncResult {
+                source_table: name.to_string(),
+                target_table: name.to_string(),
+                success,
+                records_synced,
+                records_failed: 0,
+                records_unchanged: 0,
+                duration_ms,
+                error: (!success).then(|| "timeout".to_string()),
+                error_code: None,
+                retryable: false,
+                dry_run: false,
+                sql: Vec::new(),
+                attempts: 1,
+                last_retry_reason: None,
+            };
+        let run = |duration_ms: u64, tables: Vec<TableSyncResult>| SyncResult {
+            success: tables.iter().all(|t| t.success),
+            mode: "incremental".to_string(),
+            tables: tables
+                .into_iter()
+                .map(|t| (t.source_table.clone(), t))
+                .collect(),
+            duration_ms,
+            completed_at: "2024-01-01T00:00:00Z".to_string(),
+            error: None,
+        };
+        let previous = run(
+            1000,
+            vec![
+                table("users", 100, 400, true),
+                table("orders", 0, 300, false),
+                table("events", 50, 300, true),
+            ],
+        );
+        let current = run(
+            1800,
+            vec![
+                table("users", 120, 1200, true),
+                table("orders", 30, 200, true),
+                table("events", 0, 100, false),
+                table("sessions", 10, 300, true),
+            ],
+        );
+
+        let delta = current.delta(&previous);
+        assert_eq!(delta.records_synced, 10);
+        assert_eq!(delta.duration_ms, 800);
+        assert_eq!(
+            delta.tables["users"],
+            TableDelta {
+                records_synced: 20,
+                duration_ms: 800,
+                previous_duration_ms: Some(400),
+            }
+        );
+        assert_eq!(delta.tables["sessions"].previous_duration_ms, None);
+        assert_eq!(delta.tables["sessions"].records_synced, 10);
+        assert_eq!(delta.newly_failing, ["events"]);
+        assert_eq!(delta.recovered, ["orders"]);
+
+        let unchanged = current.delta(&current);
+        assert!(unchanged.newly_failing.is_empty() && unchanged.recovered.is_empty());
+        assert!(unchanged.tables.values().all(|t| t.duration_ms == 0));
+    }
+
+    /// In-memory source whose rows count as unsynced until marked.
+    struct MemorySource {
+        rows: Vec<HashMap<String, serde_json::Value>>,
+        marked: Arc<Mutex<Vec<Vec<serde_json::Value>>>>,
+        /// Type of the sync flag column, if the table has one
+        flag_type: Option<&'static str>,
+        /// Name of the sync flag column
+        flag_column: &'static str,
+        indexes: Vec<Index>,
+        /// Batches fetched through cursors so far
+        fetches: Arc<std::sync::atomic::AtomicUsize>,
+        /// How long each cursor fetch takes
+        fetch_delay: Duration,
+        /// `fetch_rows` calls left to fail with a dropped connection
+        fetch_failures: std::sync::atomic::AtomicUsize,
+        /// `reconnect` calls left to fail with a refused connection
+        reconnect_failures: usize,
+    }
+
+    impl MemorySource {
+        /// `count` rows with ids from 1.
+        fn new(count: i64, flag_type: Option<&'static str>) -> Self {
+            MemorySource {
+                rows: (1..=count)
+                    .map(|id| {
+                        HashMap::from([
+                            ("id".to_string(), serde_json::json!(id)),
+                            ("name".to_string(), serde_json::json!(format!("e{}", id))),
+                        ])
+                    })
+                    .collect(),
+                marked: Arc::new(Mutex::new(Vec::new())),
+                flag_type,
+                flag_column: "synced_to_motherduck",
+                indexes: Vec::new(),
+                fetches: Arc::default(),
+                fetch_delay: Duration::ZERO,
+                fetch_failures: Default::default(),
+                reconnect_failures: 0,
+            }
+        }
+
+        fn unsynced(&self) -> Vec<HashMap<String, serde_json::Value>> {
+            let marked = self.marked.lock().unwrap();
+            self.rows
+                .iter()
+                .filter(|r| !marked.contains(&vec![r["id"].clone()]))
+                .cloned()
+                .collect()
+        }
+
+        /// The rows a fetch returns: all of them for a full sync, else the
+        /// unsynced ones past `since` on the watermark column, in its order.
+        fn fetch(
+            &self,
+            mapping: &TableMapping,
+            full_sync: bool,
+            since: Option<&serde_json::Value>,
+        ) -> Vec<HashMap<String, serde_json::Value>> {
+            if full_sync {
+                return self.rows.clone();
+            }
+            let mut rows = self.unsynced();
+            if let Some(ref col) = mapping.watermark_column {
+                rows.retain(|r| since.is_none_or(|since| watermark_gt(&r[col], since)));
+                rows.sort_by(|a, b| a[col].as_str().cmp(&b[col].as_str()));
+            }
+            rows
+        }
+    }
+
+    /// Cursor over a [`MemorySource`]'s unsynced rows, applying marks on close.
+    struct MemoryCursor {
+        rows: Vec<HashMap<String, serde_json::Value>>,
+        pending: Mutex<Vec<Vec<serde_json::Value>>>,
+        marked: Arc<Mutex<Vec<Vec<serde_json::Value>>>>,
+        fetches: Arc<std::sync::atomic::AtomicUsize>,
+        fetch_delay: Duration,
+    }
+
+    #[async_trait::async_trait]
+    impl SourceCursor for MemoryCursor {
+        async fn next_batch(
+            &mut self,
+            size: usize,
+        ) -> Result<Vec<HashMap<String, serde_json::Value>>> {
+            tokio::time::sleep(self.fetch_delay).await;
+            self.fetches
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let size = size.min(self.rows.len());
+            Ok(self.rows.drain(..size).collect())
+        }
+
+        async fn mark_synced(&self, keys: &[Vec<serde_json::Value>]) -> Result<u64> {
+            self.pending.lock().unwrap().extend_from_slice(keys);
+            Ok(keys.len() as u64)
+        }
+
+        async fn close(self: Box<Self>) -> Result<()> {
+            let pending = self.pending.into_inner().unwrap();
+            self.marked.lock().unwrap().extend(pending);
+            Ok(())
+        }
+
+        async fn abort(self: Box<Self>) {}
+    }
+
+    #[async_trait::async_trait]
+    impl SyncSource for MemorySource {
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn reconnect(&mut self) -> Result<()> {
+            if self.reconnect_failures > 0 {
+                self.reconnect_failures -= 1;
+                return Err(Error::Io(std::io::Error::other("connection refused")));
+            }
+            Ok(())
+        }
+
+        async fn open_cursor<'a>(
+            &'a self,
+            mapping: &'a TableMapping,
+            full_sync: bool,
+            _limit: Option<usize>,
+            since: Option<&serde_json::Value>,
+        ) -> Result<Box<dyn SourceCursor + 'a>> {
+            Ok(Box::new(MemoryCursor {
+                rows: self.fetch(mapping, full_sync, since),
+                pending: Mutex::new(Vec::new()),
+                marked: self.marked.clone(),
+                fetches: self.fetches.clone(),
+                fetch_delay: self.fetch_delay,
+            }))
+        }
+
+        async fn table_exists(&self, table: &str) -> Result<bool> {
+            Ok(table.starts_with("events"))
+        }
+
+        // The statements PostgreSQL would run, so `--show-sql` output can be checked
+        fn fetch_statement(
+            &self,
+            mapping: &TableMapping,
+            full_sync: bool,
+            has_since: bool,
+        ) -> Option<String> {
+            Some(PostgresClient::fetch_statement(
+                mapping, full_sync, has_since,
+            ))
+        }
+
+        async fn introspect_indexes(&self, _table: &str) -> Result<Vec<Index>> {
+            Ok(self.indexes.clone())
+        }
+
+        async fn introspect_table(&self, _table: &str) -> Result<Vec<IntrospectedColumn>> {
+            let column = |name: &str, pg_type: &str| IntrospectedColumn {
+                name: name.to_string(),
+                pg_type: pg_type.to_string(),
+                nullable: name != "id",
+                default: None,
+                is_primary_key: name == "id",
+            };
+            let mut columns = vec![column("id", "bigint"), column("name", "text")];
+            columns.extend(
+                self.flag_type
+                    .map(|flag_type| column(self.flag_column, flag_type)),
+            );
+            Ok(columns)
+        }
+
+        async fn fetch_rows(
+            &self,
+            mapping: &TableMapping,
+            full_sync: bool,
+            _limit: Option<usize>,
+            since: Option<&serde_json::Value>,
+        ) -> Result<Vec<HashMap<String, serde_json::Value>>> {
+            let failing = self.fetch_failures.fetch_update(
+                std::sync::atomic::Ordering::SeqCst,
+                std::sync::atomic::Ordering::SeqCst,
+                |left| left.checked_sub(1),
+            );
+            if failing.is_ok() {
+                return Err(Error::Io(std::io::Error::other("connection reset")));
+            }
+            Ok(self.fetch(mapping, full_sync, since))
+        }
+
+        async fn mark_synced(
+            &self,
+            _mapping: &TableMapping,
+            keys: &[Vec<serde_json::Value>],
+        ) -> Result<u64> {
+            self.marked.lock().unwrap().extend_from_slice(keys);
+            Ok(keys.len() as u64)
+        }
+
+        async fn unsynced_count(
+            &self,
+            _mapping: &TableMapping,
+            _since: Option<&serde_json::Value>,
+        ) -> Result<i64> {
+            Ok(self.unsynced().len() as i64)
+        }
+
+        async fn count_rows(&self, _table: &str, _filter: Option<&str>) -> Result<i64> {
+            Ok(self.rows.len() as i64)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_with_in_memory_source() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let source = MemorySource::new(3, Some("boolean"));
+        let marked = source.marked.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_rows = Arc::new(Mutex::new(0));
+        let seen = target_rows.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                *seen.lock().unwrap() = sink.count_rows(&mapping.target_table)?;
+                Ok(())
+            });
+
+        assert_eq!(client.get_unsynced_counts().await.unwrap()["events"], 3);
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_records(), 3);
+        assert_eq!(*target_rows.lock().unwrap(), 3);
+        assert_eq!(marked.lock().unwrap().len(), 3);
+
+        let again = client.sync(SyncMode::Incremental).await.unwrap();
+        assert_eq!(again.total_records(), 0);
+        assert_eq!(*target_rows.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_writes_nothing() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let source = MemorySource::new(3, Some("boolean"));
+        let marked = source.marked.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_exists = Arc::new(Mutex::new(None));
+        let seen = target_exists.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                *seen.lock().unwrap() = Some(sink.table_exists(&mapping.target_table)?);
+                Ok(())
+            });
+
+        let result = client.sync(SyncMode::DryRun).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.mode, "dry-run");
+        let table = &result.tables["events"];
+        assert!(table.dry_run);
+        assert_eq!(table.records_synced, 3);
+        assert_eq!(*target_exists.lock().unwrap(), Some(false));
+        assert!(marked.lock().unwrap().is_empty());
+
+        // Nothing was marked, so a real run still finds every row
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert_eq!(result.total_records(), 3);
+        assert!(!result.tables["events"].dry_run);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_between_tables_keeps_partial_result() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = ["events", "events_archive"]
+            .into_iter()
+            .map(|table| {
+                TableMapping::builder()
+                    .source_table(table)
+                    .primary_key(["id"])
+                    .enabled(true)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        let source = MemorySource::new(3, Some("boolean"));
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let cancel = CancellationToken::new();
+        let trigger = cancel.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |_, _, _| {
+                trigger.cancel();
+                Ok(())
+            });
+
+        let err = client
+            .sync_with_cancel(SyncMode::Full, cancel)
+            .await
+            .unwrap_err();
+        let Error::Cancelled {
+            partial: Some(partial),
+        } = err
+        else {
+            panic!(
+                "expected a cancelled sync with its partial result, got {:?}",
+                err
+            );
+        };
+        assert_eq!(partial.tables.len(), 1);
+        assert_eq!(partial.tables["events"].records_synced, 3);
+        assert_eq!(partial.error.as_deref(), Some("Sync cancelled"));
+
+        // An already-cancelled token stops before the first table
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let err = client
+            .sync_with_cancel(SyncMode::Full, cancel)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::Cancelled { partial: Some(ref p) } if p.tables.is_empty()),
+            "{:?}",
+            err
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetches_default_to_primary_key_order() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["name", "id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+            TableMapping::builder()
+                .source_table("events_by_time")
+                .primary_key(["id"])
+                .order_by("created_at DESC")
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let source = MemorySource::new(3, Some("boolean"));
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client =
+            SyncClient::with_backends(config, Box::new(source), Box::new(sink)).with_sql_capture();
+
+        let result = client.sync(SyncMode::DryRun).await.unwrap();
+        let fetch = |table: &str| {
+            result.tables[table]
+                .sql
+                .iter()
+                .find(|s| s.starts_with("SELECT"))
+                .cloned()
+                .unwrap()
+        };
+        assert!(
+            fetch("events").ends_with(r#"ORDER BY "name", "id""#),
+            "{}",
+            fetch("events")
+        );
+        assert!(fetch("events_by_time").ends_with("ORDER BY created_at DESC"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_source_fails_the_table() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = ["evnets", "events"]
+            .into_iter()
+            .map(|table| {
+                TableMapping::builder()
+                    .source_table(table)
+                    .primary_key(["id"])
+                    .enabled(true)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+
+        let source = MemorySource::new(3, Some("boolean"));
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_exists = Arc::new(Mutex::new(HashMap::new()));
+        let seen = target_exists.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                let exists = sink.table_exists(&mapping.target_table)?;
+                seen.lock()
+                    .unwrap()
+                    .insert(mapping.target_table.clone(), exists);
+                Ok(())
+            });
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(!result.success);
+        let missing = &result.tables["evnets"];
+        assert!(!missing.success);
+        assert_eq!(missing.error_code.as_deref(), Some("SOURCE_NOT_FOUND"));
+        assert_eq!(
+            missing.error.as_deref(),
+            Some("Source table 'evnets' not found in database")
+        );
+        // No target DDL ran for it, and the other table still synced
+        assert!(!target_exists.lock().unwrap()["evnets"]);
+        assert!(result.tables["events"].success);
+        assert_eq!(result.tables["events"].records_synced, 3);
+    }
+
+    #[tokio::test]
+    async fn test_hard_delete_with_mapped_primary_key() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .map_column("id", "event_id")
+                .hard_delete(true)
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_rows = Arc::new(Mutex::new(0));
+        let seen = target_rows.clone();
+        let client = SyncClient::with_backends(
+            config,
+            Box::new(MemorySource::new(3, Some("boolean"))),
+            Box::new(sink),
+        )
+        // A row the source no longer has
+        .with_pre_table_hook(|mapping, sink| {
+            sink.execute(&format!(
+                "INSERT INTO {} (id, name) VALUES (99, 'gone')",
+                mapping.target_table
+            ))
+            .map(|_| ())
+        })
+        .with_table_hook(move |mapping, _, sink| {
+            *seen.lock().unwrap() = sink.count_rows(&mapping.target_table)?;
+            Ok(())
+        });
+
+        let result = client.sync(SyncMode::Full).await.unwrap();
+        assert!(result.success, "{:?}", result.tables["events"].error);
+        assert_eq!(result.total_records(), 3);
+        assert_eq!(*target_rows.lock().unwrap(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_table_retries_are_reported() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.retry = fast_retry(2);
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let source = MemorySource::new(3, None);
+        source
+            .fetch_failures
+            .store(1, std::sync::atomic::Ordering::SeqCst);
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        let result = client.sync(SyncMode::Full).await.unwrap();
+        let events = &result.tables["events"];
+        assert!(events.success, "{:?}", events.error);
+        assert_eq!(events.records_synced, 3);
+        assert_eq!(events.attempts, 2);
+        assert!(
+            events
+                .last_retry_reason
+                .as_deref()
+                .is_some_and(|reason| reason.contains("connection reset"))
+        );
+
+        // The count starts over for the next run
+        let result = client.sync(SyncMode::Full).await.unwrap();
+        assert_eq!(result.tables["events"].attempts, 1);
+        assert_eq!(result.tables["events"].last_retry_reason, None);
+    }
+
+    #[tokio::test]
+    async fn test_row_hash_resync_counts_unchanged_rows() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .row_hash(true)
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client =
+            SyncClient::with_backends(config, Box::new(MemorySource::new(3, None)), Box::new(sink));
+
+        let first = client.sync(SyncMode::Full).await.unwrap();
+        assert!(first.success, "{:?}", first.tables["events"].error);
+        assert_eq!(first.tables["events"].records_synced, 3);
+        assert_eq!(first.tables["events"].records_unchanged, 0);
+
+        // The same rows again: nothing is written, and nothing fails
+        let second = client.sync(SyncMode::Full).await.unwrap();
+        assert!(second.success, "{:?}", second.tables["events"].error);
+        assert_eq!(second.tables["events"].records_synced, 0);
+        assert_eq!(second.tables["events"].records_unchanged, 3);
+        assert_eq!(second.tables["events"].records_failed, 0);
+        assert_eq!(second.total_records(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pipelined_streaming() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.streaming = true;
+        config.sync.pipeline_depth = 2;
+        config.sync.batch_size = 2;
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let source = MemorySource::new(7, Some("boolean"));
+        let marked = source.marked.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_rows = Arc::new(Mutex::new(0));
+        let seen = target_rows.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                *seen.lock().unwrap() = sink.count_rows(&mapping.target_table)?;
+                Ok(())
+            });
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_records(), 7);
+        assert_eq!(*target_rows.lock().unwrap(), 7);
+        assert_eq!(marked.lock().unwrap().len(), 7);
+
+        let again = client.sync(SyncMode::Incremental).await.unwrap();
+        assert_eq!(again.total_records(), 0);
+    }
+
+    /// Sink whose upserts take a while, recording how many batches the source
+    /// had fetched before and after each one. Its pings can be made to fail,
+    /// counting the reconnects that follow.
+    #[derive(Default)]
+    struct SlowSink {
+        fetches: Arc<std::sync::atomic::AtomicUsize>,
+        writes: Arc<Mutex<Vec<(usize, usize)>>>,
+        /// `ping` calls left to fail with a lost connection
+        ping_failures: std::sync::atomic::AtomicUsize,
+        reconnects: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl SyncSink for SlowSink {
+        fn ping(&self) -> Result<()> {
+            let failing = self.ping_failures.fetch_update(
+                std::sync::atomic::Ordering::SeqCst,
+                std::sync::atomic::Ordering::SeqCst,
+                |left| left.checked_sub(1),
+            );
+            if failing.is_ok() {
+                return Err(Error::Io(std::io::Error::other("connection lost")));
+            }
+            Ok(())
+        }
+
+        fn reconnect(&mut self) -> Result<()> {
+            self.reconnects
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+
+        fn table_exists(&self, _table: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        fn ensure_table(
+            &self,
+            _table: &str,
+            _columns: &[IntrospectedColumn],
+            _primary_key: &[String],
+            _indexes: &[Index],
+            _type_overrides: &HashMap<String, String>,
+        ) -> Result<()> {
+            Ok(())
+        }
+
+        fn upsert(
+            &self,
+            _mapping: &TableMapping,
+            rows: &[HashMap<String, serde_json::Value>],
+        ) -> Result<usize> {
+            use std::sync::atomic::Ordering;
+            let before = self.fetches.load(Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            let after = self.fetches.load(Ordering::SeqCst);
+            self.writes.lock().unwrap().push((before, after));
+            Ok(rows.len())
+        }
+
+        fn delete_rows(
+            &self,
+            _mapping: &TableMapping,
+            keys: &[Vec<serde_json::Value>],
+        ) -> Result<usize> {
+            Ok(keys.len())
+        }
+
+        fn delete_missing(
+            &self,
+            _mapping: &TableMapping,
+            _live_keys: &[Vec<serde_json::Value>],
+        ) -> Result<usize> {
+            Ok(0)
+        }
+
+        fn count_rows(&self, _table: &str) -> Result<i64> {
+            Ok(0)
+        }
+
+        fn drop_table(&self, _table: &str) -> Result<()> {
+            Ok(())
+        }
+
+        fn get_watermark(&self, _table: &str) -> Result<Option<serde_json::Value>> {
+            Ok(None)
+        }
+
+        fn set_watermark(
+            &self,
+            _table: &str,
+            _watermark: &serde_json::Value,
+            _records_synced: usize,
+            _sync_mode: &str,
+        ) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_retries_source() {
+        use std::sync::atomic::Ordering;
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.retry = fast_retry(2);
+
+        let mut source = MemorySource::new(0, None);
+        source.reconnect_failures = 1;
+        let sink = SlowSink::default();
+        let reconnects = sink.reconnects.clone();
+        let mut client =
+            SyncClient::with_backends(config.clone(), Box::new(source), Box::new(sink));
+
+        client.reconnect().await.unwrap();
+        assert_eq!(client.metrics_snapshot().retries, 1);
+        assert_eq!(reconnects.load(Ordering::SeqCst), 1);
+
+        // A source that stays down gives up before touching the sink
+        let mut source = MemorySource::new(0, None);
+        source.reconnect_failures = 5;
+        let sink = SlowSink::default();
+        let reconnects = sink.reconnects.clone();
+        let mut client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        match client.reconnect().await {
+            Err(Error::RetryExhausted { attempts, .. }) => assert_eq!(attempts, 3),
+            other => panic!("expected RetryExhausted, got {:?}", other),
+        }
+        assert_eq!(reconnects.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sync_reconnects_lost_sink() {
+        use std::sync::atomic::Ordering;
+        let config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+
+        let sink = SlowSink::default();
+        sink.ping_failures.store(1, Ordering::SeqCst);
+        let reconnects = sink.reconnects.clone();
+        let client =
+            SyncClient::with_backends(config, Box::new(MemorySource::new(0, None)), Box::new(sink));
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(reconnects.load(Ordering::SeqCst), 1);
+
+        // The connection answers now, so it is kept
+        client.sync(SyncMode::Incremental).await.unwrap();
+        assert_eq!(reconnects.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_pipelined_fetches_overlap_writes() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.batch_size = 2;
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        for (depth, overlaps) in [(0, false), (2, true)] {
+            config.sync.streaming = true;
+            config.sync.pipeline_depth = depth;
+            let mut source = MemorySource::new(8, Some("boolean"));
+            source.fetch_delay = Duration::from_millis(10);
+            let sink = SlowSink {
+                fetches: source.fetches.clone(),
+                ..SlowSink::default()
+            };
+            let writes = sink.writes.clone();
+            let client =
+                SyncClient::with_backends(config.clone(), Box::new(source), Box::new(sink));
+
+            let result = client.sync(SyncMode::Incremental).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert_eq!(result.total_records(), 8);
+            let writes = writes.lock().unwrap();
+            assert_eq!(writes.len(), 4);
+            // Serially the source sits idle while a batch is written
+            assert_eq!(
+                writes.iter().any(|(before, after)| after > before),
+                overlaps,
+                "depth {}: {:?}",
+                depth,
+                writes
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_incremental_sync_checks_sync_flag() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+        let client = |flag_type| {
+            let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+            SyncClient::with_backends(
+                config.clone(),
+                Box::new(MemorySource::new(2, flag_type)),
+                Box::new(sink),
+            )
+        };
+
+        for (flag_type, message) in [
+            (
+                None,
+                "Sync flag column synced_to_motherduck not found in events",
+            ),
+            (
+                Some("integer"),
+                "Sync flag column synced_to_motherduck of events is integer, not boolean",
+            ),
+        ] {
+            let result = client(flag_type).sync(SyncMode::Incremental).await.unwrap();
+            let table = &result.tables["events"];
+            assert!(!table.success);
+            assert!(
+                table.error.as_ref().unwrap().contains(message),
+                "{:?}",
+                table.error
+            );
+            assert_eq!(table.error_code.as_deref(), Some("VALIDATION_ERROR"));
+
+            // Full syncs never filter or mark on the flag
+            let result = client(flag_type).sync(SyncMode::Full).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert_eq!(result.total_records(), 2);
+        }
+
+        let result = client(Some("boolean"))
+            .sync(SyncMode::Incremental)
+            .await
+            .unwrap();
+        assert!(result.success, "{:?}", result.error);
+
+        // A source that reports no columns can't vouch for the flag either
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client = SyncClient::with_backends(
+            config.clone(),
+            Box::new(SlowCountSource::default()),
+            Box::new(sink),
+        );
+        let err = client.check_sync_flag(&config.tables[0]).await.unwrap_err();
+        assert!(matches!(err, Error::Validation(_)), "{:?}", err);
+        assert!(
+            err.to_string()
+                .contains("Sync flag column synced_to_motherduck not found in events")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_sync_flag_stays_out_of_target() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.auto_migrate = true;
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .sync_flag_column("is_synced")
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+        let mut source = MemorySource::new(2, Some("boolean"));
+        source.flag_column = "is_synced";
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let flag_columns = Arc::new(Mutex::new(Vec::new()));
+        let seen = flag_columns.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                let select = format!("SELECT is_synced FROM {}", mapping.target_table);
+                seen.lock().unwrap().push(sink.execute(&select).is_ok());
+                Ok(())
+            });
+
+        // Created without the flag, then left without it by auto_migrate
+        for _ in 0..2 {
+            let result = client.sync(SyncMode::Incremental).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+        }
+        assert_eq!(*flag_columns.lock().unwrap(), [false, false]);
+    }
+
+    #[tokio::test]
+    async fn test_strict_schema_ignores_custom_sync_flag() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.strict_schema = true;
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .sync_flag_column("is_synced")
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+        let mut source = MemorySource::new(2, Some("boolean"));
+        source.flag_column = "is_synced";
+        // A target column that happens to share the flag's name isn't compared
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        sink.execute(
+            "CREATE TABLE events (id BIGINT PRIMARY KEY, name VARCHAR, is_synced VARCHAR)",
+        )
+        .unwrap();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.tables["events"].error);
+        assert_eq!(result.total_records(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_swap_skips_custom_sync_flag_indexes() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .sync_flag_column("is_synced")
+                .full_refresh_strategy(FullRefreshStrategy::AtomicSwap)
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+        let mut source = MemorySource::new(3, Some("boolean"));
+        source.flag_column = "is_synced";
+        source.indexes = vec![
+            Index::new("events_name", vec!["name".to_string()]),
+            Index::new("events_unsynced", vec!["is_synced".to_string()]),
+        ];
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        for mode in [SyncMode::Incremental, SyncMode::Full] {
+            let result = client.sync(mode).await.unwrap();
+            assert!(
+                result.success,
+                "{}: {:?}",
+                mode, result.tables["events"].error
+            );
+        }
+    }
+
+    /// Source whose counts take a while, recording how many ran at once.
+    #[derive(Default)]
+    struct SlowCountSource {
+        in_flight: std::sync::atomic::AtomicUsize,
+        peak: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl SyncSource for SlowCountSource {
+        async fn ping(&self) -> Result<()> {
+            Ok(())
+        }
+
+        async fn table_exists(&self, _table: &str) -> Result<bool> {
+            Ok(true)
+        }
+
+        async fn introspect_table(&self, _table: &str) -> Result<Vec<IntrospectedColumn>> {
+            Ok(Vec::new())
+        }
+
+        async fn fetch_rows(
+            &self,
+            _mapping: &TableMapping,
+            _full_sync: bool,
+            _limit: Option<usize>,
+            _since: Option<&serde_json::Value>,
+        ) -> Result<Vec<HashMap<String, serde_json::Value>>> {
+            Ok(Vec::new())
+        }
+
+        async fn mark_synced(
+            &self,
+            _mapping: &TableMapping,
+            keys: &[Vec<serde_json::Value>],
+        ) -> Result<u64> {
+            Ok(keys.len() as u64)
+        }
+
+        async fn unsynced_count(
+            &self,
+            mapping: &TableMapping,
+            _since: Option<&serde_json::Value>,
+        ) -> Result<i64> {
+            use std::sync::atomic::Ordering;
+            let running = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(mapping.source_table.len() as i64)
+        }
+
+        async fn count_rows(&self, _table: &str, _filter: Option<&str>) -> Result<i64> {
+            Ok(0)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unsynced_counts_run_concurrently() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .postgres_pool_size(3)
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = ["a", "bb", "ccc", "dddd", "eeeee", "ffffff", "g"]
+            .into_iter()
+            .map(|table| {
+                TableMapping::builder()
+                    .source_table(table)
+                    .primary_key(["id"])
+                    .enabled(table != "g")
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let source = SlowCountSource::default();
+        let peak = source.peak.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        let counts = client.get_unsynced_counts().await.unwrap();
+        assert_eq!(counts.len(), 6);
+        assert_eq!(counts["dddd"], 4);
+        assert!(!counts.contains_key("g"));
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_null_primary_keys_are_skipped() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        let mapping = TableMapping::builder()
+            .source_table("events")
+            .primary_key(["id"])
+            .enabled(true)
+            .build()
+            .unwrap();
+        config.tables = vec![mapping.clone()];
+
+        let mut source = MemorySource::new(3, Some("boolean"));
+        source.rows.push(HashMap::from([
+            ("id".to_string(), serde_json::Value::Null),
+            ("name".to_string(), serde_json::json!("orphan")),
+        ]));
+        assert!(matches!(
+            without_rejected_rows(&mapping, &source.rows[..3], &config.sync),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            without_rejected_rows(&mapping, &source.rows, &config.sync).len(),
+            3
+        );
+
+        let marked = source.marked.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let target_rows = Arc::new(Mutex::new(0));
+        let seen = target_rows.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                *seen.lock().unwrap() = sink.count_rows(&mapping.target_table)?;
+                Ok(())
+            });
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_records(), 3);
+        assert_eq!(result.total_failed(), 1);
+        assert_eq!(*target_rows.lock().unwrap(), 3);
+        assert_eq!(marked.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_null_key_rows_hold_the_watermark() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .watermark_column("name")
+                .no_sync_flag()
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        for streaming in [false, true] {
+            config.sync.streaming = streaming;
+            let mut source = MemorySource::new(4, None);
+            source.rows.push(HashMap::from([
+                ("id".to_string(), serde_json::Value::Null),
+                ("name".to_string(), serde_json::json!("e2b")),
+            ]));
+            let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+            let stored = Arc::new(Mutex::new(None));
+            let seen = stored.clone();
+            let client =
+                SyncClient::with_backends(config.clone(), Box::new(source), Box::new(sink))
+                    .with_table_hook(move |mapping, _, sink| {
+                        *seen.lock().unwrap() = sink.get_watermark(&mapping.target_table)?;
+                        Ok(())
+                    });
+
+            let result = client.sync(SyncMode::Incremental).await.unwrap();
+            assert!(result.success, "{:?}", result.error);
+            assert_eq!(result.total_records(), 4);
+            assert_eq!(result.total_failed(), 1);
+            assert_eq!(*stored.lock().unwrap(), Some(serde_json::json!("e2")));
+
+            // The skipped row is fetched again, with the rows past it
+            let again = client.sync(SyncMode::Incremental).await.unwrap();
+            assert_eq!(again.total_records(), 2, "streaming: {}", streaming);
+            assert_eq!(again.total_failed(), 1);
+            assert_eq!(*stored.lock().unwrap(), Some(serde_json::json!("e2")));
+        }
+    }
+
+    #[test]
+    fn test_oversized_fields_are_limited() {
+        let mapping = TableMapping::builder()
+            .source_table("profiles")
+            .primary_key(["id"])
+            .oversized_field("avatar", OversizedFieldPolicy::Null)
+            .build()
+            .unwrap();
+        let mut rows = vec![HashMap::from([
+            ("id".to_string(), serde_json::json!(1)),
+            ("bio".to_string(), serde_json::json!("é".repeat(50))),
+            ("avatar".to_string(), serde_json::json!("a".repeat(100))),
+            ("tags".to_string(), serde_json::json!(["x".repeat(100)])),
+            ("note".to_string(), serde_json::json!("short")),
+        ])];
+
+        let mut oversized = OversizedValues::new();
+        limit_field_sizes(
+            &mapping,
+            &mut rows,
+            64,
+            OversizedFieldPolicy::Truncate,
+            &mut oversized,
+        );
+        let bio = rows[0]["bio"].as_str().unwrap();
+        assert!(bio.len() <= 64 && bio.ends_with(TRUNCATED_MARKER));
+        assert!(bio.starts_with("éé"));
+        assert!(rows[0]["avatar"].is_null());
+        assert_eq!(
+            rows[0]["tags"],
+            serde_json::json!({"_truncated": true, "bytes": 104})
+        );
+        assert_eq!(rows[0]["note"], "short");
+        // Counted per column for the table's summary
+        assert_eq!(
+            oversized,
+            OversizedValues::from([
+                (("avatar".to_string(), "written as NULL"), 1),
+                (("bio".to_string(), "truncated"), 1),
+                (("tags".to_string(), "truncated"), 1),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_fields_can_fail_rows() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.max_field_bytes = Some(64);
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .oversized_field("name", OversizedFieldPolicy::Fail)
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let mut source = MemorySource::new(3, Some("boolean"));
+        source.rows[1].insert("name".to_string(), serde_json::json!("b".repeat(65)));
+        let marked = source.marked.clone();
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink));
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_records(), 2);
+        assert_eq!(result.total_failed(), 1);
+        assert_eq!(
+            *marked.lock().unwrap(),
+            [vec![serde_json::json!(1)], vec![serde_json::json!(3)]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_rows_hold_the_watermark() {
+        let mut config = SyncConfig::builder()
+            .postgres_url("postgres://postgres@127.0.0.1:1/postgres")
+            .motherduck_local_path(":memory:")
+            .build()
+            .unwrap();
+        config.sync.max_field_bytes = Some(8);
+        config.tables = vec![
+            TableMapping::builder()
+                .source_table("events")
+                .primary_key(["id"])
+                .watermark_column("name")
+                .no_sync_flag()
+                .enabled(true)
+                .build()
+                .unwrap(),
+        ];
+
+        let mut source = MemorySource::new(4, None);
+        source.rows.push(HashMap::from([
+            ("id".to_string(), serde_json::json!(5)),
+            ("name".to_string(), serde_json::json!("e2 with a long tail")),
+        ]));
+        let sink = MotherDuckClient::connect(config.motherduck.clone()).unwrap();
+        let stored = Arc::new(Mutex::new(None));
+        let seen = stored.clone();
+        let client = SyncClient::with_backends(config, Box::new(source), Box::new(sink))
+            .with_table_hook(move |mapping, _, sink| {
+                *seen.lock().unwrap() = sink.get_watermark(&mapping.target_table)?;
+                Ok(())
+            });
+
+        let result = client.sync(SyncMode::Incremental).await.unwrap();
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.total_records(), 4);
+        assert_eq!(result.total_failed(), 1);
+        assert_eq!(*stored.lock().unwrap(), Some(serde_json::json!("e2")));
+
+        // The skipped row is fetched again, with the rows past it
+        let again = client.sync(SyncMode::Incremental).await.unwrap();
+        assert_eq!(again.total_records(), 2);
+        assert_eq!(again.total_failed(), 1);
+        assert_eq!(*stored.lock().unwrap(), Some(serde_json::json!("e2")));
     }
 }
@@ -0,0 +1,70 @@
+//! OpenTelemetry trace export (`otel` feature).
+//!
+//! The `#[instrument]` spans on the sync path (`sync`, `sync_table`,
+//! `fetch_rows`, `batch_upsert`, ...) are ordinary `tracing` spans; the layer
+//! returned by [`otlp_layer`] forwards them to an OTLP/HTTP collector.
+//!
+//! ```no_run
+//! use tracing_subscriber::prelude::*;
+//!
+//! # fn main() -> motherduck_supasync::Result<()> {
+//! let (otel, _guard) = motherduck_supasync::telemetry::otlp_layer("http://localhost:4318/v1/traces")?;
+//! tracing_subscriber::registry().with(otel).init();
+//! // Spans are flushed when `_guard` is dropped
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::{Error, Result};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Service name reported on exported spans.
+const SERVICE_NAME: &str = "motherduck-supasync";
+
+/// Layer exporting spans through OpenTelemetry.
+pub type OtelLayer<S> = OpenTelemetryLayer<S, SdkTracer>;
+
+/// Flushes pending spans and shuts the exporter down when dropped.
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("Failed to flush OpenTelemetry spans: {}", e);
+        }
+    }
+}
+
+/// Build a layer exporting spans to the OTLP/HTTP collector at `endpoint`.
+///
+/// Spans are exported in batches from a background thread; keep the returned
+/// guard alive until the process is done tracing.
+pub fn otlp_layer<S>(endpoint: &str) -> Result<(OtelLayer<S>, OtelGuard)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .build()
+        .map_err(|e| Error::config_with_source("Failed to build OTLP exporter", e))?;
+
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+    let tracer = provider.tracer(SERVICE_NAME);
+
+    Ok((
+        tracing_opentelemetry::layer().with_tracer(tracer),
+        OtelGuard { provider },
+    ))
+}
@@ -0,0 +1,91 @@
+//! Write throttling for `sync.max_rows_per_second`.
+//!
+//! A token bucket refilled at the configured rate, holding at most one second's
+//! worth of rows. A batch larger than the bucket is allowed through but leaves
+//! it in debt, so the next batch waits until the debt is paid off.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Token-bucket limiter counting rows.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rows_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Rows that may be written right now; negative while in debt
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Allow `rows_per_second` rows per second, starting with a full bucket.
+    pub fn new(rows_per_second: u32) -> Self {
+        let rows_per_second = f64::from(rows_per_second.max(1));
+        Self {
+            rows_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: rows_per_second,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take `rows` from the bucket, returning how long to wait before writing them.
+    pub fn reserve(&self, rows: usize) -> Duration {
+        self.reserve_at(rows, Instant::now())
+    }
+
+    /// Block the current thread until `rows` may be written.
+    pub fn throttle(&self, rows: usize) {
+        let wait = self.reserve(rows);
+        if !wait.is_zero() {
+            debug!("Throttling {} rows for {:?}", rows, wait);
+            std::thread::sleep(wait);
+        }
+    }
+
+    fn reserve_at(&self, rows: usize, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+
+        let elapsed = now.saturating_duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rows_per_second).min(self.rows_per_second);
+        bucket.updated = now;
+        bucket.tokens -= rows as f64;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rows_per_second)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_reserve() {
+        let limiter = RateLimiter::new(1000);
+        let start = limiter.bucket.lock().unwrap().updated;
+
+        // The first second's worth goes through immediately
+        assert_eq!(limiter.reserve_at(1000, start), Duration::ZERO);
+        // The bucket is empty, so 500 more rows wait half a second
+        assert_eq!(limiter.reserve_at(500, start), Duration::from_millis(500));
+
+        // After the wait the debt is paid and the bucket refills from zero
+        let later = start + Duration::from_millis(500);
+        assert_eq!(limiter.reserve_at(250, later), Duration::from_millis(250));
+
+        // Idle time never refills past one second's worth
+        let idle = later + Duration::from_secs(10);
+        assert_eq!(limiter.reserve_at(1000, idle), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(500, idle), Duration::from_millis(500));
+    }
+}